@@ -1,6 +1,5 @@
-use anyhow::{bail, Result};
-
-use crate::{APIError, ActionList, GetActionList};
+use crate::api::get_json;
+use crate::{ActionList, GetActionList, Midgard, MidgardError};
 
 /// Action List
 /// List actions along with their related transactions. An action is generated by one or more inbound transactions with the intended action set in the transaction memo. The action may result in one or more outbound transactions. Results are paginated by sets of 50. Filters may be applied to query actions.
@@ -56,7 +55,7 @@ use crate::{APIError, ActionList, GetActionList};
 /// 2. JSON Parsing Error
 /// 3. Faild to Parse URL Parameters
 #[allow(clippy::module_name_repetitions)]
-pub async fn api_get_action_list(base_url: &str, parameters: GetActionList) -> Result<ActionList> {
+pub async fn api_get_action_list(midgard: &Midgard, parameters: GetActionList) -> Result<ActionList, MidgardError> {
 	let mut params = vec![];
 
 	params.push(("asset", parameters.asset.join(",")));
@@ -96,26 +95,11 @@ pub async fn api_get_action_list(base_url: &str, parameters: GetActionList) -> R
 		params.push(("fromHeight", from_height.to_string()));
 	}
 
-	let mut endpoint = base_url.to_string() + "actions";
+	let mut endpoint = "actions".to_string();
 	if !params.is_empty() {
 		endpoint.push('?');
 		endpoint.push_str(&serde_urlencoded::to_string(params)?);
 	}
 
-	let response = match reqwest::get(&endpoint).await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let response = match response.text().await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let res: ActionList = match serde_json::from_str(&response) {
-		Ok(res) => res,
-		Err(e) => bail!(APIError::SerdeError(e)),
-	};
-
-	Ok(res)
+	get_json(midgard, &endpoint).await
 }
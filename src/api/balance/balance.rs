@@ -1,16 +1,15 @@
-use anyhow::{bail, Result};
-
-use crate::{APIError, Balance};
+use crate::api::get_json;
+use crate::{Balance, Midgard, MidgardError};
 
 /// # Errors
 /// 1. Network Request Failed
 /// 2. JSON Parsing Error
 /// 3. Faild to Parse URL Parameters
 #[allow(clippy::module_name_repetitions)]
-pub async fn api_get_balance(base_url: &str, address: &str, timestamp: Option<i64>, height: Option<u64>) -> Result<Balance> {
-	let mut endpoint = base_url.to_string() + "balance/" + address;
+pub async fn api_get_balance(midgard: &Midgard, address: &str, timestamp: Option<i64>, height: Option<u64>) -> Result<Balance, MidgardError> {
+	let mut endpoint = "balance/".to_string() + address;
 	if timestamp.is_some() && height.is_some() {
-		bail!(APIError::InvalidParameter("Only one of timestamp or height can be specified, not both, if both are specified the request will fail.".to_string()));
+		return Err(MidgardError::InvalidParameter("Only one of timestamp or height can be specified, not both, if both are specified the request will fail.".to_string()));
 	}
 	if timestamp.is_some() || height.is_some() {
 		endpoint.push('?');
@@ -23,20 +22,5 @@ pub async fn api_get_balance(base_url: &str, address: &str, timestamp: Option<i6
 		}
 	}
 
-	let response = match reqwest::get(&endpoint).await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let response = match response.text().await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let res: Balance = match serde_json::from_str(&response) {
-		Ok(res) => res,
-		Err(e) => bail!(APIError::SerdeError(e)),
-	};
-
-	Ok(res)
+	get_json(midgard, &endpoint).await
 }
@@ -1,29 +1,13 @@
-use anyhow::{bail, Result};
-
-use crate::{APIError, BorrowersDetails};
+use crate::api::get_json;
+use crate::{BorrowersDetails, Midgard, MidgardError};
 
 /// # Errors
 /// 1. Network Request Failed
 /// 2. JSON Parsing Error
 /// 3. Faild to Parse URL Parameters
 #[allow(clippy::module_name_repetitions)]
-pub async fn api_get_borrowers_details(base_url: &str, address: &str) -> Result<BorrowersDetails> {
-	let endpoint = base_url.to_string() + "borrower/" + address;
-
-	let response = match reqwest::get(&endpoint).await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let response = match response.text().await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let res: BorrowersDetails = match serde_json::from_str(&response) {
-		Ok(res) => res,
-		Err(e) => bail!(APIError::SerdeError(e)),
-	};
+pub async fn api_get_borrowers_details(midgard: &Midgard, address: &str) -> Result<BorrowersDetails, MidgardError> {
+	let endpoint = "borrower/".to_string() + address;
 
-	Ok(res)
+	get_json(midgard, &endpoint).await
 }
@@ -1,27 +1,11 @@
-use anyhow::{bail, Result};
-
-use crate::{APIError, ChurnsList};
+use crate::api::get_json;
+use crate::{ChurnsList, Midgard, MidgardError};
 
 /// # Errors
 /// 1. Network Request Failed
 /// 2. JSON Parsing Error
 #[allow(clippy::module_name_repetitions)]
-pub async fn api_get_churn_list(base_url: &str) -> Result<ChurnsList> {
-	let endpoint = base_url.to_string() + "churns";
-	let response = match reqwest::get(&endpoint).await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let response = match response.text().await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let res: ChurnsList = match serde_json::from_str(&response) {
-		Ok(res) => res,
-		Err(e) => bail!(APIError::SerdeError(e)),
-	};
-
-	Ok(res)
+pub async fn api_get_churn_list(midgard: &Midgard) -> Result<ChurnsList, MidgardError> {
+	let endpoint = "churns".to_string();
+	get_json(midgard, &endpoint).await
 }
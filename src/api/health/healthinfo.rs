@@ -1,27 +1,11 @@
-use anyhow::{bail, Result};
-
-use crate::{APIError, HealthInfo};
+use crate::api::get_json;
+use crate::{HealthInfo, Midgard, MidgardError};
 
 /// # Errors
 /// 1. Network Request Failed
 /// 2. JSON Parsing Error
 #[allow(clippy::module_name_repetitions)]
-pub async fn api_get_health_info(base_url: &str) -> Result<HealthInfo> {
-	let endpoint = base_url.to_string() + "health";
-	let response = match reqwest::get(&endpoint).await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let response = match response.text().await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let res: HealthInfo = match serde_json::from_str(&response) {
-		Ok(res) => res,
-		Err(e) => bail!(APIError::SerdeError(e)),
-	};
-
-	Ok(res)
+pub async fn api_get_health_info(midgard: &Midgard) -> Result<HealthInfo, MidgardError> {
+	let endpoint = "health".to_string();
+	get_json(midgard, &endpoint).await
 }
@@ -1,6 +1,7 @@
 pub use depth_and_price_history::*;
 pub use earnings_history::*;
 pub use liquidity_change_history::*;
+pub use query::*;
 pub use savers_units_and_depth_history::*;
 pub use swaps_history::*;
 pub use total_value_locked_history::*;
@@ -8,6 +9,7 @@ pub use total_value_locked_history::*;
 mod depth_and_price_history;
 mod earnings_history;
 mod liquidity_change_history;
+mod query;
 mod savers_units_and_depth_history;
 mod swaps_history;
 mod total_value_locked_history;
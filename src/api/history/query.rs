@@ -0,0 +1,79 @@
+use crate::{HistoryQuery, MidgardError};
+
+/// Builds the endpoint (path and query string) shared by every history endpoint.
+/// Every parameter that is given ends up in the query string, whether or not an interval or count is set.
+///
+/// # Errors
+/// 1. Faild to Parse URL Parameters
+pub fn history_endpoint(path: &str, pool: Option<&str>, query: &HistoryQuery) -> Result<String, MidgardError> {
+	let mut params = query.params();
+	if let Some(pool) = pool {
+		params.push(("pool", pool.to_string()));
+	}
+
+	let mut endpoint = path.to_string();
+	if !params.is_empty() {
+		endpoint.push('?');
+		endpoint.push_str(&serde_urlencoded::to_string(params)?);
+	}
+
+	Ok(endpoint)
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::{DateTime, TimeZone, Utc};
+
+	use super::*;
+	use crate::Interval;
+
+	fn at(timestamp: i64) -> DateTime<Utc> {
+		Utc.timestamp_opt(timestamp, 0).unwrap()
+	}
+
+	#[test]
+	fn test_no_parameters() {
+		assert_eq!(history_endpoint("history/swaps", None, &HistoryQuery::default()).unwrap(), "history/swaps");
+	}
+
+	#[test]
+	fn test_pool_only() {
+		assert_eq!(history_endpoint("history/swaps", Some("BTC.BTC"), &HistoryQuery::default()).unwrap(), "history/swaps?pool=BTC.BTC");
+	}
+
+	#[test]
+	fn test_time_frame_without_interval() {
+		let query = HistoryQuery::builder().range(at(1_606_780_899)..at(1_608_825_600)).build().unwrap();
+		assert_eq!(history_endpoint("history/swaps", Some("BTC.BTC"), &query).unwrap(), "history/swaps?to=1608825600&from=1606780899&pool=BTC.BTC");
+
+		let query = HistoryQuery::builder().from(at(1_606_780_899)).build().unwrap();
+		assert_eq!(history_endpoint("history/earnings", None, &query).unwrap(), "history/earnings?from=1606780899");
+
+		let query = HistoryQuery::builder().to(at(1_608_825_600)).build().unwrap();
+		assert_eq!(history_endpoint("history/tvl", None, &query).unwrap(), "history/tvl?to=1608825600");
+	}
+
+	#[test]
+	fn test_interval_and_count() {
+		let query = HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap();
+		assert_eq!(history_endpoint("history/depths/BTC.BTC", None, &query).unwrap(), "history/depths/BTC.BTC?interval=day&count=10");
+
+		let query = HistoryQuery::builder().interval(Interval::Hour).build().unwrap();
+		assert_eq!(history_endpoint("history/savers/BTC.BTC", None, &query).unwrap(), "history/savers/BTC.BTC?interval=hour");
+
+		let query = HistoryQuery::builder().interval(Interval::Week).count(10).to(at(1_608_825_600)).build().unwrap();
+		assert_eq!(history_endpoint("history/earnings", None, &query).unwrap(), "history/earnings?interval=week&count=10&to=1608825600");
+
+		let query = HistoryQuery::builder().interval(Interval::Day).count(10).from(at(1_606_780_800)).build().unwrap();
+		assert_eq!(history_endpoint("history/tvl", None, &query).unwrap(), "history/tvl?interval=day&count=10&from=1606780800");
+	}
+
+	#[test]
+	fn test_all_parameters() {
+		let query = HistoryQuery::builder().interval(Interval::FiveMinutes).count(400).to(at(1_608_825_600)).build().unwrap();
+		assert_eq!(history_endpoint("history/liquidity_changes", Some("ETH.ETH"), &query).unwrap(), "history/liquidity_changes?interval=5min&count=400&to=1608825600&pool=ETH.ETH");
+
+		let query = HistoryQuery::builder().interval(Interval::FiveMinutes).range(at(1_606_780_800)..at(1_608_825_600)).build().unwrap();
+		assert_eq!(history_endpoint("history/liquidity_changes", Some("ETH.ETH"), &query).unwrap(), "history/liquidity_changes?interval=5min&to=1608825600&from=1606780800&pool=ETH.ETH");
+	}
+}
@@ -9,6 +9,7 @@ pub use members::*;
 pub use network::*;
 pub use nodes::*;
 pub use pools::*;
+pub use request::*;
 pub use savers::*;
 pub use thorname::*;
 
@@ -23,5 +24,6 @@ mod members;
 mod network;
 mod nodes;
 mod pools;
+mod request;
 mod savers;
 mod thorname;
@@ -1,28 +1,12 @@
-use anyhow::{bail, Result};
-
-use crate::{APIError, NodeList};
+use crate::api::get_json;
+use crate::{Midgard, MidgardError, NodeList};
 
 /// # Errors
 /// 1. Network Request Failed
 /// 2. JSON Parsing Error
 /// 3. Faild to Parse URL Parameters
 #[allow(clippy::module_name_repetitions)]
-pub async fn api_get_node_list(base_url: &str) -> Result<NodeList> {
-	let endpoint = base_url.to_string() + "nodes";
-	let response = match reqwest::get(&endpoint).await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let response = match response.text().await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let res: NodeList = match serde_json::from_str(&response) {
-		Ok(res) => res,
-		Err(e) => bail!(APIError::SerdeError(e)),
-	};
-
-	Ok(res)
+pub async fn api_get_node_list(midgard: &Midgard) -> Result<NodeList, MidgardError> {
+	let endpoint = "nodes".to_string();
+	get_json(midgard, &endpoint).await
 }
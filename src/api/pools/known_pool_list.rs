@@ -1,27 +1,11 @@
-use anyhow::{bail, Result};
-
-use crate::{APIError, KnownPoolList};
+use crate::api::get_json;
+use crate::{KnownPoolList, Midgard, MidgardError};
 
 /// # Errors
 /// 1. Network Request Failed
 /// 2. JSON Parsing Error
 #[allow(clippy::module_name_repetitions)]
-pub async fn api_get_known_pool_list(base_url: &str) -> Result<KnownPoolList> {
-	let endpoint = base_url.to_string() + "knownpools";
-	let response = match reqwest::get(&endpoint).await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let response = match response.text().await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let res: KnownPoolList = match serde_json::from_str(&response) {
-		Ok(res) => res,
-		Err(e) => bail!(APIError::SerdeError(e)),
-	};
-
-	Ok(res)
+pub async fn api_get_known_pool_list(midgard: &Midgard) -> Result<KnownPoolList, MidgardError> {
+	let endpoint = "knownpools".to_string();
+	get_json(midgard, &endpoint).await
 }
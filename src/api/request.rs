@@ -0,0 +1,760 @@
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::de::DeserializeOwned;
+
+use crate::runtime::{self, Instant};
+use crate::trace::event;
+use crate::{AuthProvider, CassetteMode, Configuration, Midgard, MidgardError, RequestInfo, ResponseInfo, TransportRequest};
+
+/// Sends a GET request for `endpoint` (relative to the configured base url) and deserializes the JSON response.
+/// Responses are served from and stored in the response cache when it is enabled.
+/// Every attempt waits for the configured rate limits. Failed attempts are retried according to the configured `RetryPolicy`.
+/// With the `tracing` feature the endpoint, cache hits, attempts and time spent waiting for the rate limiter are recorded on the span of the calling `Midgard::get_*` method.
+///
+/// # Errors
+/// 1. Network Request Failed
+/// 2. Non-2xx Response, carrying the server message when Midgard sent one
+/// 3. JSON Parsing Error, including the path of the field that failed to parse
+pub async fn get_json<T: DeserializeOwned>(midgard: &Midgard, endpoint: &str) -> Result<T, MidgardError> {
+	#[cfg(feature = "tracing")]
+	tracing::Span::current().record("endpoint", endpoint);
+
+	let (status, body) = if let Some(response) = midgard.cached_response(endpoint) {
+		#[cfg(feature = "tracing")]
+		tracing::Span::current().record("cache_hit", true);
+		event!(debug, endpoint, "served from the response cache");
+		midgard.get_request_metrics().record_cache_hit(endpoint);
+		response
+	} else {
+		let (status, body) = fetch_with_retries(midgard, endpoint).await?;
+		midgard.cache_response(endpoint, status, &body);
+		(status, body)
+	};
+
+	let deserializer = &mut serde_json::Deserializer::from_str(&body);
+	match serde_path_to_error::deserialize(deserializer) {
+		Ok(res) => Ok(res),
+		Err(e) => {
+			let error = MidgardError::parse(endpoint, status, &body, e);
+			event!(warn, endpoint, status, %error, "failed to parse response");
+			midgard.get_request_metrics().record_error(endpoint, &error);
+			Err(error)
+		}
+	}
+}
+
+/// Fetches `endpoint`, retrying failed attempts according to the configured `RetryPolicy`.
+/// With a cassette recording, only the response of the last attempt is written to it, so a replay sees the outcome of the call rather than every retried failure.
+async fn fetch_with_retries(midgard: &Midgard, endpoint: &str) -> Result<(u16, String), MidgardError> {
+	let policy = midgard.get_config().get_retry_policy();
+
+	let mut waited = Duration::ZERO;
+	let mut last_response = None;
+	let mut attempt = 1;
+	let result = loop {
+		match fetch_with_failover(midgard, endpoint, &mut waited, &mut last_response).await {
+			Ok(response) => break Ok(response),
+			Err((error, retry_after)) if attempt < policy.get_max_attempts() && policy.is_retryable(&error) => {
+				let delay = policy.delay(attempt, retry_after);
+				event!(warn, endpoint, attempt, delay_ms = delay.as_millis(), %error, "retrying request");
+				midgard.get_request_metrics().record_retry(endpoint);
+				runtime::sleep(delay).await;
+				attempt += 1;
+			}
+			Err((error, _)) => break Err(error),
+		}
+	};
+
+	#[cfg(feature = "tracing")]
+	tracing::Span::current().record("attempts", attempt).record("rate_limit_wait_ms", u64::try_from(waited.as_millis()).unwrap_or(u64::MAX));
+	if let (Some(cassette), Some((url, status, body))) = (midgard.get_config().get_cassette(), last_response) {
+		midgard.get_tapes().record(cassette.get_path(), endpoint, &url, status, &body).await?;
+	}
+	result
+}
+
+/// Tries the configured base urls in the order given by the routing mode until one answers without a connection error, timeout or 5xx status.
+/// Instances that fail this way are marked unhealthy and skipped by later requests until a background probe finds them healthy again.
+/// The time spent waiting for the rate limiter is added to `waited`, and `last_response` is set as described for `fetch`.
+/// Replayed requests never reach Midgard, so they skip the rate limiter and failover.
+async fn fetch_with_failover(midgard: &Midgard, endpoint: &str, waited: &mut Duration, last_response: &mut Option<(String, u16, String)>) -> Result<(u16, String), (MidgardError, Option<Duration>)> {
+	if midgard.get_config().get_cassette().is_some_and(|cassette| cassette.get_mode() == CassetteMode::Replay) {
+		return fetch(midgard, midgard.get_config().get_base_url(), endpoint, last_response).await;
+	}
+
+	let base_urls = midgard.base_urls_for_request().await;
+	if base_urls.is_empty() {
+		return Err((MidgardError::Inconsistent { endpoint: endpoint.to_string(), height: midgard.get_router().read_height() }, None));
+	}
+	let last = base_urls.len() - 1;
+	for (index, base_url) in base_urls.iter().enumerate() {
+		let waiting = Instant::now();
+		midgard.wait_for_rate_limit(endpoint).await;
+		let wait = waiting.elapsed();
+		midgard.get_request_metrics().record_rate_limit_wait(endpoint, wait);
+		*waited += wait;
+		let result = fetch(midgard, base_url, endpoint, last_response).await;
+		match &result {
+			Ok(_) => {
+				midgard.rate_limit_ok(endpoint);
+				midgard.get_router().mark_reachable(base_url);
+			}
+			Err((error, retry_after)) if error.status() == Some(429) => {
+				event!(info, endpoint, retry_after_ms = retry_after.map(|delay| delay.as_millis()), "rate limited by Midgard");
+				midgard.rate_limited(endpoint, *retry_after);
+			}
+			Err((error, _)) if is_unreachable(error) => {
+				midgard.get_router().mark_unreachable(base_url);
+				if index < last {
+					event!(warn, endpoint, base_url, %error, "failing over to the next base url");
+					continue;
+				}
+			}
+			Err(_) => {}
+		}
+		return result;
+	}
+	unreachable!("base_urls is not empty")
+}
+
+/// Returns true for errors that suggest the instance itself is down rather than the request being wrong.
+fn is_unreachable(error: &MidgardError) -> bool {
+	matches!(error, MidgardError::Request { .. } | MidgardError::Transport { .. } | MidgardError::Timeout { .. }) || error.status().is_some_and(|status| status >= 500)
+}
+
+/// Performs a single attempt against `base_url`, returning the status and body of a 2xx response.
+/// The attempt fails with `MidgardError::Timeout` once the configured total timeout has passed, or the read timeout while waiting for the headers or a chunk of the body.
+/// Middleware sees the request before it is sent and its outcome afterwards.
+/// On failure the server's `Retry-After` hint is returned alongside the error.
+/// With a cassette recording, `last_response` is set to the url, status and body of the response, or cleared when none was received.
+async fn fetch(midgard: &Midgard, base_url: &str, endpoint: &str, last_response: &mut Option<(String, u16, String)>) -> Result<(u16, String), (MidgardError, Option<Duration>)> {
+	let mut request = RequestInfo::new("GET", endpoint, base_url.to_string() + endpoint);
+	for middleware in midgard.get_middleware() {
+		middleware.before_request(&mut request);
+	}
+
+	let timeouts = midgard.get_config().get_timeouts();
+	let started = Instant::now();
+	let attempt = fetch_once(midgard, &request, timeouts.read());
+	let response = match timeouts.total() {
+		Some(total) => runtime::timeout(total, attempt).await.unwrap_or_else(|_| Err(MidgardError::Timeout { endpoint: endpoint.to_string() })),
+		None => attempt.await,
+	};
+	let latency = started.elapsed();
+
+	if midgard.get_config().get_cassette().is_some_and(|cassette| cassette.get_mode() == CassetteMode::Record) {
+		*last_response = response.as_ref().ok().map(|(status, body, _)| (request.get_url().to_string(), *status, body.clone()));
+	}
+
+	let body_size = response.as_ref().map_or(0, |(_, body, _)| body.len());
+	let result = match response {
+		Ok((status, body, _)) if (200..300).contains(&status) => Ok((status, body)),
+		Ok((status, body, retry_after)) => Err((MidgardError::from_status(endpoint, status, &body), retry_after)),
+		Err(error) => Err((error, None)),
+	};
+
+	let outcome = match &result {
+		Ok((status, _)) => ResponseInfo::new(Some(*status), latency, body_size, None),
+		Err((error, _)) => ResponseInfo::new(error.status(), latency, body_size, Some(error)),
+	};
+	for middleware in midgard.get_middleware().iter().rev() {
+		middleware.after_response(&request, &outcome);
+	}
+	midgard.get_request_metrics().record_request(endpoint, latency, outcome.get_error());
+	result
+}
+
+/// Sends `request` and reads the whole response, returning its status, body and `Retry-After` hint whatever the status.
+/// With a cassette replaying, the response is served from it instead of sending the request.
+async fn fetch_once(midgard: &Midgard, request: &RequestInfo, read_timeout: Option<Duration>) -> Result<(u16, String, Option<Duration>), MidgardError> {
+	match midgard.get_config().get_cassette() {
+		Some(cassette) if cassette.get_mode() == CassetteMode::Replay => {
+			let (status, body) = midgard.get_tapes().replay(cassette.get_path(), request.get_endpoint()).await?;
+			Ok((status, body, None))
+		}
+		_ => send(midgard, request, read_timeout).await,
+	}
+}
+
+/// Sends `request` through the transport of `midgard` and reads the whole response.
+async fn send(midgard: &Midgard, request: &RequestInfo, read_timeout: Option<Duration>) -> Result<(u16, String, Option<Duration>), MidgardError> {
+	let endpoint = request.get_endpoint();
+	let headers = request_headers(midgard.get_config(), request.get_headers())?;
+
+	let response = midgard.get_transport().send(TransportRequest::new(request.get_method(), request.get_url().to_string(), headers, read_timeout)).await.map_err(|error| MidgardError::transport(endpoint, error))?;
+
+	let status = response.get_status();
+	let retry_after = response.get_headers().get(reqwest::header::RETRY_AFTER).and_then(|value| value.to_str().ok()).and_then(parse_retry_after);
+	Ok((status, String::from_utf8_lossy(response.get_body()).into_owned(), retry_after))
+}
+
+/// Builds the headers of a request: the configured default headers, the headers of `auth` and of the auth provider, which are marked sensitive, and the headers added by middleware.
+pub fn request_headers(config: &Configuration, extra: &[(String, String)]) -> Result<HeaderMap, MidgardError> {
+	let invalid = |name: &str| MidgardError::InvalidParameter(format!("Invalid header {name}"));
+
+	let mut headers = HeaderMap::new();
+	for (name, value) in config.get_headers() {
+		headers.insert(HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid(name))?, HeaderValue::from_str(value).map_err(|_| invalid(name))?);
+	}
+
+	let auth_headers = config.get_auth().map(AuthProvider::headers).into_iter().chain(config.get_auth_provider().map(|provider| provider.headers())).flatten();
+	for (name, secret) in auth_headers {
+		let mut value = HeaderValue::from_str(secret.expose()).map_err(|_| invalid(&name))?;
+		value.set_sensitive(true);
+		headers.insert(HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid(&name))?, value);
+	}
+
+	for (name, value) in extra {
+		headers.insert(HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid(name))?, HeaderValue::from_str(value).map_err(|_| invalid(name))?);
+	}
+	Ok(headers)
+}
+
+/// Parses a `Retry-After` header value, given either in seconds or as an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+	let value = value.trim();
+	if let Ok(seconds) = value.parse::<u64>() {
+		return Some(Duration::from_secs(seconds));
+	}
+	let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+	(date.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().ok().or(Some(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::{Arc, Mutex};
+	use std::time::Instant;
+
+	use serde_json::Value;
+
+	use super::*;
+	use crate::test_support::{MockMidgard, MockResponse};
+	use crate::{Auth, CachePolicy, Cassette, EndpointGroup, EndpointMetrics, FailoverPolicy, Middleware, RetryPolicy, Routing, Secret, Timeouts, TimingMiddleware};
+
+	fn midgard(server: &MockMidgard, max_attempts: u32) -> Midgard {
+		let mut policy = RetryPolicy::new(max_attempts, 1, 10);
+		policy.set_jitter(false);
+		let mut config = server.get_config();
+		config.set_retry_policy(policy);
+		Midgard::with_config(config)
+	}
+
+	fn health_at(height: u64) -> MockResponse {
+		MockResponse::new(200, &format!(r#"{{"inSync":true,"scannerHeight":"{height}","lastCommitted":{{"height":{height},"timestamp":0}}}}"#))
+	}
+
+	#[test]
+	fn test_parse_retry_after() {
+		assert_eq!(parse_retry_after("2"), Some(Duration::from_secs(2)));
+		assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+		assert_eq!(parse_retry_after("soon"), None);
+	}
+
+	#[tokio::test]
+	async fn test_retries_transient_failures() {
+		let server = MockMidgard::start().await;
+		server.set_responses("health", vec![MockResponse::new(503, "unavailable"), MockResponse::new(502, "bad gateway"), MockResponse::new(200, r#"{"ok":true}"#)]);
+		let midgard = midgard(&server, 3);
+
+		let res: Value = get_json(&midgard, "health").await.unwrap();
+
+		assert_eq!(res["ok"], true);
+		assert_eq!(server.get_requests().len(), 3);
+	}
+
+	#[tokio::test]
+	async fn test_gives_up_after_max_attempts() {
+		let server = MockMidgard::start().await;
+		server.set_response("health", 429, r#"{"error":"rate limited"}"#);
+		let midgard = midgard(&server, 2);
+
+		let error = get_json::<Value>(&midgard, "health").await.unwrap_err();
+
+		assert_eq!(error.status(), Some(429));
+		assert_eq!(error.server_message(), Some("rate limited"));
+		assert_eq!(server.get_requests().len(), 2);
+	}
+
+	#[tokio::test]
+	async fn test_does_not_retry_client_errors() {
+		let server = MockMidgard::start().await;
+		server.set_response("pool/NOT.A.POOL", 404, r#"{"error":"pool not found"}"#);
+		let midgard = midgard(&server, 3);
+
+		let error = get_json::<Value>(&midgard, "pool/NOT.A.POOL").await.unwrap_err();
+
+		assert!(matches!(error, MidgardError::Api { status: 404, .. }));
+		assert_eq!(server.get_requests().len(), 1);
+	}
+
+	#[tokio::test]
+	async fn test_honors_retry_after() {
+		let server = MockMidgard::start().await;
+		server.set_responses("health", vec![MockResponse::new(429, "").with_header("Retry-After", "1"), MockResponse::new(200, "{}")]);
+		let midgard = midgard(&server, 2);
+
+		let started = Instant::now();
+		get_json::<Value>(&midgard, "health").await.unwrap();
+
+		assert!(started.elapsed() >= Duration::from_secs(1));
+		assert_eq!(server.get_requests().len(), 2);
+	}
+
+	#[tokio::test]
+	async fn test_fails_over_to_next_base_url() {
+		let down = MockMidgard::start().await;
+		down.set_response("health", 503, "unavailable");
+		let up = MockMidgard::start().await;
+		up.set_response("health", 200, r#"{"ok":true}"#);
+		let mut midgard = midgard(&down, 1);
+		let mut config = midgard.get_config().clone();
+		config.set_base_urls(vec![down.get_base_url().to_string(), up.get_base_url().to_string()]);
+		config.set_failover_policy(FailoverPolicy::new(3_600_000, 100));
+		midgard.set_config(config);
+
+		let res: Value = get_json(&midgard, "health").await.unwrap();
+		assert_eq!(res["ok"], true);
+
+		// the failed instance is skipped until a probe finds it healthy again
+		get_json::<Value>(&midgard, "health").await.unwrap();
+		assert_eq!(down.get_requests().len(), 1);
+		assert_eq!(up.get_requests().len(), 2);
+	}
+
+	#[tokio::test]
+	async fn test_does_not_fail_over_on_client_errors() {
+		let first = MockMidgard::start().await;
+		first.set_response("pool/NOT.A.POOL", 404, r#"{"error":"not found"}"#);
+		let second = MockMidgard::start().await;
+		let mut midgard = midgard(&first, 1);
+		let mut config = midgard.get_config().clone();
+		config.set_base_urls(vec![first.get_base_url().to_string(), second.get_base_url().to_string()]);
+		midgard.set_config(config);
+
+		assert!(get_json::<Value>(&midgard, "pool/NOT.A.POOL").await.is_err());
+		assert_eq!(second.get_requests().len(), 0);
+	}
+
+	#[tokio::test]
+	async fn test_routes_to_highest_committed_height() {
+		let behind = MockMidgard::start().await;
+		behind.set_responses("health", vec![health_at(100)]);
+		behind.set_response("pools", 200, r#"{"server":"behind"}"#);
+		let ahead = MockMidgard::start().await;
+		ahead.set_responses("health", vec![health_at(105)]);
+		ahead.set_response("pools", 200, r#"{"server":"ahead"}"#);
+		let mut midgard = midgard(&behind, 1);
+		let mut config = midgard.get_config().clone();
+		let mut policy = FailoverPolicy::new(3_600_000, 100);
+		policy.set_routing(Routing::HighestCommitted { max_lag: 10 });
+		config.set_base_urls(vec![behind.get_base_url().to_string(), ahead.get_base_url().to_string()]);
+		config.set_failover_policy(policy);
+		midgard.set_config(config);
+
+		let res: Value = get_json(&midgard, "pools").await.unwrap();
+
+		assert_eq!(res["server"], "ahead");
+		assert_eq!(behind.get_requests().len(), 1);
+		assert_eq!(midgard.get_router().read_height(), 105);
+	}
+
+	#[tokio::test]
+	async fn test_probes_time_out_hung_instances() {
+		let hung = MockMidgard::start().await;
+		hung.set_responses("health", vec![health_at(105).with_delay(Duration::from_secs(5))]);
+		let up = MockMidgard::start().await;
+		up.set_responses("health", vec![health_at(100)]);
+		up.set_response("pools", 200, r#"{"server":"up"}"#);
+		let mut midgard = midgard(&hung, 1);
+		let mut config = midgard.get_config().clone();
+		let mut policy = FailoverPolicy::new(3_600_000, 100);
+		policy.set_routing(Routing::HighestCommitted { max_lag: 10 });
+		config.set_base_urls(vec![hung.get_base_url().to_string(), up.get_base_url().to_string()]);
+		config.set_failover_policy(policy);
+		config.set_timeouts(Timeouts::new(None, None, Some(200)));
+		midgard.set_config(config);
+
+		let started = Instant::now();
+		let res: Value = get_json(&midgard, "pools").await.unwrap();
+
+		assert_eq!(res["server"], "up");
+		assert!(started.elapsed() < Duration::from_secs(2));
+	}
+
+	#[tokio::test]
+	async fn test_never_reads_below_previous_height() {
+		let server = MockMidgard::start().await;
+		server.set_responses("health", vec![health_at(105), health_at(100)]);
+		server.set_response("pools", 200, "{}");
+		let mut midgard = midgard(&server, 1);
+		let mut config = midgard.get_config().clone();
+		let mut policy = FailoverPolicy::new(3_600_000, 100);
+		policy.set_routing(Routing::HighestCommitted { max_lag: 10 });
+		config.set_failover_policy(policy);
+		midgard.set_config(config);
+		get_json::<Value>(&midgard, "pools").await.unwrap();
+
+		// the instance went back to height 100, e.g. after a restart
+		midgard.get_router().probe_all(midgard.get_transport(), midgard.get_config()).await;
+		let error = get_json::<Value>(&midgard, "pools").await.unwrap_err();
+
+		assert!(matches!(error, MidgardError::Inconsistent { height: 105, .. }));
+	}
+
+	#[tokio::test]
+	async fn test_probes_send_auth() {
+		let server = MockMidgard::start().await;
+		server.require_header("authorization", "Bearer s3cr3t");
+		server.set_response("health", 200, r#"{"inSync":true,"scannerHeight":"105","lastCommitted":{"height":105,"timestamp":0}}"#);
+		let mut config = server.get_config();
+		let mut policy = FailoverPolicy::new(3_600_000, 100);
+		policy.set_routing(Routing::HighestCommitted { max_lag: 10 });
+		config.set_failover_policy(policy);
+		config.set_auth(Some(Auth::Bearer { token: Secret::new("s3cr3t") }));
+		let midgard = Midgard::with_config(config);
+
+		get_json::<Value>(&midgard, "pools").await.unwrap();
+
+		assert_eq!(server.get_requests(), ["health", "pools"]);
+		assert_eq!(midgard.get_router().read_height(), 105);
+	}
+
+	#[tokio::test]
+	async fn test_probes_follow_config_changes() {
+		let first = MockMidgard::start().await;
+		first.set_response("health", 200, r#"{"inSync":true,"scannerHeight":"105"}"#);
+		let old = MockMidgard::start().await;
+		let new = MockMidgard::start().await;
+		new.require_header("authorization", "Bearer rotated");
+		new.set_response("health", 200, r#"{"inSync":true,"scannerHeight":"105"}"#);
+		let mut midgard = midgard(&first, 1);
+		let mut config = midgard.get_config().clone();
+		config.set_base_urls(vec![first.get_base_url().to_string(), old.get_base_url().to_string()]);
+		config.set_failover_policy(FailoverPolicy::new(3_600_000, 100));
+		midgard.set_config(config.clone());
+		get_json::<Value>(&midgard, "health").await.unwrap();
+
+		config.set_base_urls(vec![first.get_base_url().to_string(), new.get_base_url().to_string()]);
+		config.set_failover_policy(FailoverPolicy::new(50, 100));
+		config.set_auth(Some(Auth::Bearer { token: Secret::new("rotated") }));
+		midgard.set_config(config);
+		get_json::<Value>(&midgard, "health").await.unwrap();
+
+		tokio::time::sleep(Duration::from_millis(300)).await;
+
+		assert!(!new.get_requests().is_empty());
+		assert!(old.get_requests().is_empty());
+		assert!(new.get_request_heads().iter().all(|head| head.to_lowercase().contains("authorization: bearer rotated")));
+	}
+
+	#[tokio::test]
+	async fn test_serves_cached_responses() {
+		let server = MockMidgard::start().await;
+		server.set_response("pools", 200, r#"{"ok":true}"#);
+		let mut midgard = midgard(&server, 1);
+		let mut config = midgard.get_config().clone();
+		config.set_cache_policy(CachePolicy::new(true));
+		midgard.set_config(config);
+
+		get_json::<Value>(&midgard, "pools").await.unwrap();
+		let res: Value = get_json(&midgard.clone(), "pools").await.unwrap();
+		assert_eq!(res["ok"], true);
+		assert_eq!(server.get_requests().len(), 1);
+
+		// endpoints without a TTL are not cached
+		get_json::<Value>(&midgard, "health").await.unwrap();
+		get_json::<Value>(&midgard, "health").await.unwrap();
+		assert_eq!(server.get_requests().len(), 3);
+
+		get_json::<Value>(&midgard.bypass_cache(), "pools").await.unwrap();
+		assert_eq!(server.get_requests().len(), 4);
+
+		midgard.invalidate_cache(EndpointGroup::Pools);
+		get_json::<Value>(&midgard, "pools").await.unwrap();
+		get_json::<Value>(&midgard, "pools").await.unwrap();
+		assert_eq!(server.get_requests().len(), 5);
+	}
+
+	#[tokio::test]
+	async fn test_caches_closed_history_intervals() {
+		let closed = r#"{"intervals":[],"meta":{"startTime":"1710806400","endTime":"1710892800"}}"#;
+		let open = format!(r#"{{"intervals":[],"meta":{{"startTime":"1710806400","endTime":"{}"}}}}"#, chrono::Utc::now().timestamp() + 3_600);
+		let server = MockMidgard::start().await;
+		server.set_responses("history/depths/BTC.BTC", vec![MockResponse::new(200, closed), MockResponse::new(200, &open)]);
+		let mut midgard = midgard(&server, 1);
+		let mut config = midgard.get_config().clone();
+		config.set_cache_policy(CachePolicy::new(true));
+		midgard.set_config(config);
+
+		get_json::<Value>(&midgard, "history/depths/BTC.BTC?interval=day&to=1710892800").await.unwrap();
+		get_json::<Value>(&midgard, "history/depths/BTC.BTC?interval=day&to=1710892800").await.unwrap();
+		assert_eq!(server.get_requests().len(), 1);
+
+		get_json::<Value>(&midgard, "history/depths/BTC.BTC?interval=day").await.unwrap();
+		get_json::<Value>(&midgard, "history/depths/BTC.BTC?interval=day").await.unwrap();
+		assert_eq!(server.get_requests().len(), 3);
+	}
+
+	#[tokio::test]
+	async fn test_times_out_hung_requests() {
+		let server = MockMidgard::start().await;
+		server.set_responses("health", vec![MockResponse::new(200, "{}").with_delay(Duration::from_secs(5))]);
+		let midgard = midgard(&server, 1).with_timeouts(Timeouts::new(None, None, Some(100)));
+
+		let started = Instant::now();
+		let error = get_json::<Value>(&midgard, "health").await.unwrap_err();
+
+		assert!(error.is_timeout());
+		assert_eq!(error.endpoint(), Some("health"));
+		assert!(started.elapsed() < Duration::from_secs(5));
+	}
+
+	#[tokio::test]
+	async fn test_read_timeout_and_per_call_override() {
+		let server = MockMidgard::start().await;
+		server.set_responses("health", vec![MockResponse::new(200, "{}").with_delay(Duration::from_millis(300))]);
+		let mut midgard = midgard(&server, 1);
+		let mut config = midgard.get_config().clone();
+		config.set_timeouts(Timeouts::new(None, Some(50), None));
+		midgard.set_config(config);
+
+		assert!(get_json::<Value>(&midgard, "health").await.unwrap_err().is_timeout());
+		get_json::<Value>(&midgard.with_timeouts(Timeouts::none()), "health").await.unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_sends_default_headers_and_auth() {
+		let server = MockMidgard::start().await;
+		let mut midgard = midgard(&server, 1);
+		let mut config = midgard.get_config().clone();
+		config.set_user_agent("dashboard/1.0");
+		config.set_header("x-client-id", "dashboard");
+		config.set_auth(Some(Auth::Bearer { token: Secret::new("s3cr3t") }));
+		midgard.set_config(config);
+
+		get_json::<Value>(&midgard, "health").await.unwrap();
+
+		let request = server.get_request_heads()[0].to_lowercase();
+		assert!(request.contains("user-agent: dashboard/1.0"));
+		assert!(request.contains("x-client-id: dashboard"));
+		assert!(request.contains("authorization: bearer s3cr3t"));
+		assert!(!format!("{midgard:?}").contains("s3cr3t"));
+	}
+
+	#[tokio::test]
+	async fn test_rejects_invalid_headers() {
+		let server = MockMidgard::start().await;
+		let mut midgard = midgard(&server, 3);
+		let mut config = midgard.get_config().clone();
+		config.set_header("x-client-id", "line\nbreak");
+		midgard.set_config(config);
+
+		assert!(matches!(get_json::<Value>(&midgard, "health").await, Err(MidgardError::InvalidParameter(_))));
+		assert_eq!(server.get_requests().len(), 0);
+	}
+
+	/// Redirects requests to `target` and records what it sees.
+	#[derive(Debug)]
+	struct Redirect {
+		target: String,
+		seen: Mutex<Vec<String>>,
+	}
+
+	impl Middleware for Redirect {
+		fn before_request(&self, request: &mut RequestInfo) {
+			self.seen.lock().unwrap().push(format!("before {}", request.get_endpoint()));
+			request.set_url(format!("{}{}", self.target, request.get_endpoint()));
+			request.add_header("x-trace-id", "42");
+		}
+
+		fn after_response(&self, _request: &RequestInfo, response: &ResponseInfo<'_>) {
+			self.seen.lock().unwrap().push(format!("after {:?} {}", response.get_status(), response.get_body_size()));
+		}
+	}
+
+	#[tokio::test]
+	async fn test_runs_middleware_around_attempts() {
+		let server = MockMidgard::start().await;
+		server.set_responses("health", vec![MockResponse::new(503, "down"), MockResponse::new(200, "{}")]);
+		let mut config = Configuration::new("http://127.0.0.1:9/v2/".to_string(), 0);
+		let mut policy = RetryPolicy::new(2, 1, 10);
+		policy.set_jitter(false);
+		config.set_retry_policy(policy);
+		let mut midgard = Midgard::with_config(config);
+		let redirect = Arc::new(Redirect { target: server.get_base_url().to_string(), seen: Mutex::new(vec![]) });
+		let timing = Arc::new(TimingMiddleware::new());
+		midgard.add_middleware(redirect.clone());
+		midgard.add_middleware(timing.clone());
+
+		get_json::<Value>(&midgard, "health").await.unwrap();
+
+		assert_eq!(*redirect.seen.lock().unwrap(), vec!["before health", "after Some(503) 4", "before health", "after Some(200) 2"]);
+		assert!(server.get_request_heads()[1].to_lowercase().contains("x-trace-id: 42"));
+		let health = timing.get_group_timing(EndpointGroup::Health);
+		assert_eq!((health.get_requests(), health.get_errors()), (2, 1));
+	}
+
+	#[cfg(feature = "tracing")]
+	type RecordedSpan = (&'static tracing::Metadata<'static>, Vec<(String, String)>);
+
+	/// Records spans with their fields and the messages of events.
+	#[cfg(feature = "tracing")]
+	#[derive(Default)]
+	struct Recorder {
+		spans: Mutex<Vec<RecordedSpan>>,
+		entered: Mutex<Vec<tracing::Id>>,
+		events: Mutex<Vec<String>>,
+	}
+
+	#[cfg(feature = "tracing")]
+	struct Fields<'a>(&'a mut Vec<(String, String)>);
+
+	#[cfg(feature = "tracing")]
+	impl tracing::field::Visit for Fields<'_> {
+		fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+			self.0.push((field.name().to_string(), format!("{value:?}")));
+		}
+	}
+
+	#[cfg(feature = "tracing")]
+	impl tracing::Subscriber for Recorder {
+		fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+			true
+		}
+
+		fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::Id {
+			let mut fields = vec![];
+			span.record(&mut Fields(&mut fields));
+			let mut spans = self.spans.lock().unwrap();
+			spans.push((span.metadata(), fields));
+			tracing::Id::from_u64(spans.len() as u64)
+		}
+
+		fn record(&self, span: &tracing::Id, values: &tracing::span::Record<'_>) {
+			let index = usize::try_from(span.into_u64()).unwrap() - 1;
+			values.record(&mut Fields(&mut self.spans.lock().unwrap()[index].1));
+		}
+
+		fn record_follows_from(&self, _span: &tracing::Id, _follows: &tracing::Id) {}
+
+		fn event(&self, event: &tracing::Event<'_>) {
+			let mut fields = vec![];
+			event.record(&mut Fields(&mut fields));
+			self.events.lock().unwrap().extend(fields.into_iter().filter(|(name, _)| name == "message").map(|(_, message)| message));
+		}
+
+		fn enter(&self, span: &tracing::Id) {
+			self.entered.lock().unwrap().push(span.clone());
+		}
+
+		fn exit(&self, _span: &tracing::Id) {
+			self.entered.lock().unwrap().pop();
+		}
+
+		fn current_span(&self) -> tracing_core::span::Current {
+			let Some(id) = self.entered.lock().unwrap().last().cloned() else {
+				return tracing_core::span::Current::none();
+			};
+			let index = usize::try_from(id.into_u64()).unwrap() - 1;
+			tracing_core::span::Current::new(id, self.spans.lock().unwrap()[index].0)
+		}
+	}
+
+	#[cfg(feature = "tracing")]
+	#[tokio::test]
+	async fn test_traces_calls() {
+		let server = MockMidgard::start().await;
+		server.set_responses("health", vec![MockResponse::new(503, "down"), health_at(100)]);
+		let midgard = midgard(&server, 2);
+		let recorder = Arc::new(Recorder::default());
+		let _guard = tracing::subscriber::set_default(recorder.clone());
+
+		midgard.get_health_info().await.unwrap();
+
+		let (metadata, fields) = recorder.spans.lock().unwrap().iter().find(|(metadata, _)| metadata.name() == "get_health_info").cloned().unwrap();
+		assert_eq!(metadata.level(), &tracing::Level::INFO);
+		let field = |name: &str| fields.iter().rev().find(|(field, _)| field == name).map(|(_, value)| value.clone());
+		assert_eq!(field("endpoint").as_deref(), Some("\"health\""));
+		assert_eq!(field("attempts").as_deref(), Some("2"));
+		assert!(field("rate_limit_wait_ms").is_some());
+		assert!(recorder.events.lock().unwrap().iter().any(|message| message == "retrying request"));
+	}
+
+	#[tokio::test]
+	async fn test_collects_metrics() {
+		let server = MockMidgard::start().await;
+		server.set_responses("pools", vec![MockResponse::new(503, "down"), MockResponse::new(200, "{}")]);
+		server.set_response("health", 200, "[]");
+		let mut midgard = midgard(&server, 2);
+		let mut config = midgard.get_config().clone();
+		config.set_cache_policy(CachePolicy::new(true));
+		midgard.set_config(config);
+
+		get_json::<Value>(&midgard, "pools").await.unwrap();
+		get_json::<Value>(&midgard, "pools").await.unwrap();
+		assert!(get_json::<Vec<u64>>(&midgard, "health").await.is_ok());
+		assert!(get_json::<u64>(&midgard, "health").await.is_err());
+
+		let metrics = midgard.get_metrics();
+		let pools = metrics.get_endpoint("pools");
+		assert_eq!((pools.get_requests(), pools.get_retries(), pools.get_cache_hits()), (2, 1, 1));
+		assert_eq!(pools.get_errors().get("http"), Some(&1));
+		assert_eq!(pools.get_error_count(), 1);
+		assert_eq!(pools.get_latency().get_count(), 2);
+		assert_eq!(pools.get_rate_limit_wait().get_count(), 2);
+		assert_eq!(metrics.get_endpoint("health").get_errors().get("parse"), Some(&1));
+		assert_eq!(metrics.get_total().get_requests(), 4);
+
+		midgard.reset_metrics();
+		assert_eq!(midgard.get_metrics().get_total(), EndpointMetrics::default());
+	}
+
+	#[tokio::test]
+	async fn test_records_and_replays_cassette() {
+		let server = MockMidgard::start().await;
+		server.set_response("health", 200, r#"{"ok":true}"#);
+		let path = std::env::temp_dir().join(format!("midgard-rs-request-cassette-{}.json", std::process::id()));
+		let mut config = server.get_config();
+		config.set_cassette(Some(Cassette::record(&path)));
+
+		let res: Value = get_json(&Midgard::with_config(config), "health").await.unwrap();
+		assert_eq!(res["ok"], true);
+		assert_eq!(server.get_requests().len(), 1);
+
+		// nothing listens on the discard port, and replayed requests don't wait for the rate limiter
+		let mut config = Configuration::new("http://127.0.0.1:9/v2/".to_string(), 60_000);
+		config.set_cassette(Some(Cassette::replay(&path)));
+		let midgard = Midgard::with_config(config);
+		for _ in 0..2 {
+			let res: Value = get_json(&midgard, "health").await.unwrap();
+			assert_eq!(res["ok"], true);
+		}
+		assert!(matches!(get_json::<Value>(&midgard, "pools").await, Err(MidgardError::Cassette { .. })));
+		assert_eq!(server.get_requests().len(), 1);
+
+		std::fs::remove_file(path).unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_records_only_the_final_attempt() {
+		let server = MockMidgard::start().await;
+		server.set_responses("health", vec![MockResponse::new(503, "unavailable"), MockResponse::new(200, r#"{"ok":true}"#)]);
+		let path = std::env::temp_dir().join(format!("midgard-rs-request-retried-cassette-{}.json", std::process::id()));
+		let mut midgard = midgard(&server, 3);
+		let mut config = midgard.get_config().clone();
+		config.set_cassette(Some(Cassette::record(&path)));
+		midgard.set_config(config);
+
+		let res: Value = get_json(&midgard, "health").await.unwrap();
+		assert_eq!(res["ok"], true);
+		assert_eq!(server.get_requests().len(), 2);
+
+		let cassette: Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+		let interactions = cassette["interactions"].as_array().unwrap();
+		assert_eq!(interactions.len(), 1);
+		assert_eq!(interactions[0]["status"], 200);
+
+		std::fs::remove_file(path).unwrap();
+	}
+}
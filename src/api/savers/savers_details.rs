@@ -1,30 +1,14 @@
-use anyhow::{bail, Result};
-
-use crate::{APIError, SaversDetails};
+use crate::api::get_json;
+use crate::{Midgard, MidgardError, SaversDetails};
 
 /// # Errors
 /// 1. Network Request Failed
 /// 2. JSON Parsing Error
 /// 3. Faild to Parse URL Parameters
 #[allow(clippy::module_name_repetitions)]
-pub async fn api_get_savers_details(base_url: &str, address: &[String]) -> Result<SaversDetails> {
+pub async fn api_get_savers_details(midgard: &Midgard, address: &[String]) -> Result<SaversDetails, MidgardError> {
         let address = address.join(",");
-	let endpoint = base_url.to_string() + "saver/" + &address;
-
-	let response = match reqwest::get(&endpoint).await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let response = match response.text().await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let res: SaversDetails = match serde_json::from_str(&response) {
-		Ok(res) => res,
-		Err(e) => bail!(APIError::SerdeError(e)),
-	};
+	let endpoint = "saver/".to_string() + &address;
 
-	Ok(res)
+	get_json(midgard, &endpoint).await
 }
@@ -1,29 +1,13 @@
-use anyhow::{bail, Result};
-
-use crate::{APIError, ThornameDetails};
+use crate::api::get_json;
+use crate::{Midgard, MidgardError, ThornameDetails};
 
 /// # Errors
 /// 1. Network Request Failed
 /// 2. JSON Parsing Error
 /// 3. Faild to Parse URL Parameters
 #[allow(clippy::module_name_repetitions)]
-pub async fn api_get_thorname_details(base_url: &str, name: &str) -> Result<ThornameDetails> {
-	let endpoint = base_url.to_string() + "thorname/lookup/" + name;
-
-	let response = match reqwest::get(&endpoint).await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let response = match response.text().await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let res: ThornameDetails = match serde_json::from_str(&response) {
-		Ok(res) => res,
-		Err(e) => bail!(APIError::SerdeError(e)),
-	};
+pub async fn api_get_thorname_details(midgard: &Midgard, name: &str) -> Result<ThornameDetails, MidgardError> {
+	let endpoint = "thorname/lookup/".to_string() + name;
 
-	Ok(res)
+	get_json(midgard, &endpoint).await
 }
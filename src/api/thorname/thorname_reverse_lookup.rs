@@ -1,29 +1,13 @@
-use anyhow::{bail, Result};
-
-use crate::{APIError, ThornameReverseLookup};
+use crate::api::get_json;
+use crate::{Midgard, MidgardError, ThornameReverseLookup};
 
 /// # Errors
 /// 1. Network Request Failed
 /// 2. JSON Parsing Error
 /// 3. Faild to Parse URL Parameters
 #[allow(clippy::module_name_repetitions)]
-pub async fn api_get_thorname_reverse_lookup(base_url: &str, address: &str) -> Result<ThornameReverseLookup> {
-	let endpoint = base_url.to_string() + "thorname/rlookup/" + address;
-
-	let response = match reqwest::get(&endpoint).await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let response = match response.text().await {
-		Ok(response) => response,
-		Err(e) => bail!(APIError::ReqwestError(e)),
-	};
-
-	let res: ThornameReverseLookup = match serde_json::from_str(&response) {
-		Ok(res) => res,
-		Err(e) => bail!(APIError::SerdeError(e)),
-	};
+pub async fn api_get_thorname_reverse_lookup(midgard: &Midgard, address: &str) -> Result<ThornameReverseLookup, MidgardError> {
+	let endpoint = "thorname/rlookup/".to_string() + address;
 
-	Ok(res)
+	get_json(midgard, &endpoint).await
 }
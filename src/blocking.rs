@@ -0,0 +1,364 @@
+//! A blocking client for Midgard, enabled with the `blocking` feature.
+//!
+//! `blocking::Midgard` offers every `get_*` method of the async `Midgard` as a plain function call.
+//! Like `reqwest::blocking`, it runs the requests on its own tokio runtime, so it must not be created, used or dropped from within an async runtime.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use midgard_rs::blocking::Midgard;
+//!
+//! let midgard = Midgard::new();
+//! let pools = midgard.get_pool_list(None, None).unwrap();
+//! assert!(!pools.get_pools().is_empty());
+//! ```
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use tokio::runtime::{Builder, Runtime};
+
+use crate::{ActionList, Balance, BorrowersDetails, BorrowersList, ChurnsList, Configuration, DepthHistory, EarningsHistory, GetActionList, GlobalStats, HealthInfo, HistoryQuery, Interval, KnownPoolList, LiquidityChangeHistory, MemberDetails, MemberList, MetricsSnapshot, MidgardError, NetworkData, NodeList, Pool, PoolList, PoolStatistics, PoolStatus, SaversDetails, SaversHistory, SwapHistory, TVLHistory, ThornameDetails, ThornameOwner, ThornameReverseLookup, TimePeriod};
+
+/// A blocking wrapper around the async `Midgard` client, sharing its configuration, rate limiter, cache and metrics.
+///
+/// Clones share the runtime and the state of the async client.
+#[derive(Debug, Clone)]
+pub struct Midgard {
+	inner: crate::Midgard,
+	runtime: Arc<Runtime>,
+}
+
+impl Midgard {
+	/// Creates a new instance with the default configuration.
+	///
+	/// # Panics
+	/// If the runtime can't be started.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::with_config(Configuration::default())
+	}
+
+	/// Creates a new instance using a custom configuration.
+	///
+	/// # Panics
+	/// If the runtime can't be started.
+	#[must_use]
+	pub fn with_config(config: Configuration) -> Self {
+		Self::from_async(crate::Midgard::with_config(config))
+	}
+
+	/// Creates a new instance using a caller supplied async `reqwest::Client`.
+	///
+	/// # Panics
+	/// If the runtime can't be started.
+	#[must_use]
+	pub fn with_client(config: Configuration, client: Client) -> Self {
+		Self::from_async(crate::Midgard::with_client(config, client))
+	}
+
+	/// Wraps an async client. The wrapper shares the rate limiter, cache and metrics with `midgard` and its clones.
+	///
+	/// # Panics
+	/// If the runtime can't be started.
+	#[must_use]
+	pub fn from_async(midgard: crate::Midgard) -> Self {
+		let runtime = Builder::new_multi_thread().worker_threads(1).thread_name("midgard-blocking").enable_all().build().expect("failed to start the runtime of the blocking Midgard client");
+		Self { inner: midgard, runtime: Arc::new(runtime) }
+	}
+
+	/// Returns the wrapped async client, e.g. to read its cache or failover state.
+	#[must_use]
+	pub const fn get_async(&self) -> &crate::Midgard {
+		&self.inner
+	}
+
+	#[must_use]
+	pub const fn get_config(&self) -> &Configuration {
+		self.inner.get_config()
+	}
+
+	pub fn set_config(&mut self, config: Configuration) {
+		self.inner.set_config(config);
+	}
+
+	#[must_use]
+	pub fn get_metrics(&self) -> MetricsSnapshot {
+		self.inner.get_metrics()
+	}
+
+	/// Blocking version of `crate::Midgard::get_actions`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_actions`.
+	pub fn get_actions(&self, params: GetActionList) -> Result<ActionList, MidgardError> {
+		self.runtime.block_on(self.inner.get_actions(params))
+	}
+
+	/// Blocking version of `crate::Midgard::get_balance`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_balance`.
+	pub fn get_balance(&self, address: &str, timestamp: Option<i64>, height: Option<u64>) -> Result<Balance, MidgardError> {
+		self.runtime.block_on(self.inner.get_balance(address, timestamp, height))
+	}
+
+	/// Blocking version of `crate::Midgard::get_borrowers_details`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_borrowers_details`.
+	pub fn get_borrowers_details(&self, address: &str) -> Result<BorrowersDetails, MidgardError> {
+		self.runtime.block_on(self.inner.get_borrowers_details(address))
+	}
+
+	/// Blocking version of `crate::Midgard::get_borrowers_list`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_borrowers_list`.
+	pub fn get_borrowers_list(&self, asset: Option<String>) -> Result<BorrowersList, MidgardError> {
+		self.runtime.block_on(self.inner.get_borrowers_list(asset))
+	}
+
+	/// Blocking version of `crate::Midgard::get_churn_list`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_churn_list`.
+	pub fn get_churn_list(&self) -> Result<ChurnsList, MidgardError> {
+		self.runtime.block_on(self.inner.get_churn_list())
+	}
+
+	/// Blocking version of `crate::Midgard::get_global_stats`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_global_stats`.
+	pub fn get_global_stats(&self) -> Result<GlobalStats, MidgardError> {
+		self.runtime.block_on(self.inner.get_global_stats())
+	}
+
+	/// Blocking version of `crate::Midgard::get_health_info`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_health_info`.
+	pub fn get_health_info(&self) -> Result<HealthInfo, MidgardError> {
+		self.runtime.block_on(self.inner.get_health_info())
+	}
+
+	/// Blocking version of `crate::Midgard::get_depth_and_price_history`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_depth_and_price_history`.
+	pub fn get_depth_and_price_history(&self, pool: &str, query: &HistoryQuery) -> Result<DepthHistory, MidgardError> {
+		self.runtime.block_on(self.inner.get_depth_and_price_history(pool, query))
+	}
+
+	/// Blocking version of `crate::Midgard::get_earnings_history`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_earnings_history`.
+	pub fn get_earnings_history(&self, query: &HistoryQuery) -> Result<EarningsHistory, MidgardError> {
+		self.runtime.block_on(self.inner.get_earnings_history(query))
+	}
+
+	/// Blocking version of `crate::Midgard::get_liquidity_change_history`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_liquidity_change_history`.
+	pub fn get_liquidity_change_history(&self, pool: &str, query: &HistoryQuery) -> Result<LiquidityChangeHistory, MidgardError> {
+		self.runtime.block_on(self.inner.get_liquidity_change_history(pool, query))
+	}
+
+	/// Blocking version of `crate::Midgard::get_savers_units_and_depth_history`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_savers_units_and_depth_history`.
+	pub fn get_savers_units_and_depth_history(&self, pool: &str, query: &HistoryQuery) -> Result<SaversHistory, MidgardError> {
+		self.runtime.block_on(self.inner.get_savers_units_and_depth_history(pool, query))
+	}
+
+	/// Blocking version of `crate::Midgard::get_swaps_history`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_swaps_history`.
+	pub fn get_swaps_history(&self, pool: Option<&str>, query: &HistoryQuery) -> Result<SwapHistory, MidgardError> {
+		self.runtime.block_on(self.inner.get_swaps_history(pool, query))
+	}
+
+	/// Blocking version of `crate::Midgard::get_total_value_locked_history`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_total_value_locked_history`.
+	pub fn get_total_value_locked_history(&self, query: &HistoryQuery) -> Result<TVLHistory, MidgardError> {
+		self.runtime.block_on(self.inner.get_total_value_locked_history(query))
+	}
+
+	/// Blocking version of `crate::Midgard::get_depth_and_price_history_range`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_depth_and_price_history_range`.
+	pub fn get_depth_and_price_history_range(&self, pool: &str, interval: Interval, range: Range<DateTime<Utc>>) -> Result<DepthHistory, MidgardError> {
+		self.runtime.block_on(self.inner.get_depth_and_price_history_range(pool, interval, range))
+	}
+
+	/// Blocking version of `crate::Midgard::get_earnings_history_range`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_earnings_history_range`.
+	pub fn get_earnings_history_range(&self, interval: Interval, range: Range<DateTime<Utc>>) -> Result<EarningsHistory, MidgardError> {
+		self.runtime.block_on(self.inner.get_earnings_history_range(interval, range))
+	}
+
+	/// Blocking version of `crate::Midgard::get_liquidity_change_history_range`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_liquidity_change_history_range`.
+	pub fn get_liquidity_change_history_range(&self, pool: &str, interval: Interval, range: Range<DateTime<Utc>>) -> Result<LiquidityChangeHistory, MidgardError> {
+		self.runtime.block_on(self.inner.get_liquidity_change_history_range(pool, interval, range))
+	}
+
+	/// Blocking version of `crate::Midgard::get_savers_units_and_depth_history_range`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_savers_units_and_depth_history_range`.
+	pub fn get_savers_units_and_depth_history_range(&self, pool: &str, interval: Interval, range: Range<DateTime<Utc>>) -> Result<SaversHistory, MidgardError> {
+		self.runtime.block_on(self.inner.get_savers_units_and_depth_history_range(pool, interval, range))
+	}
+
+	/// Blocking version of `crate::Midgard::get_swaps_history_range`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_swaps_history_range`.
+	pub fn get_swaps_history_range(&self, pool: Option<&str>, interval: Interval, range: Range<DateTime<Utc>>) -> Result<SwapHistory, MidgardError> {
+		self.runtime.block_on(self.inner.get_swaps_history_range(pool, interval, range))
+	}
+
+	/// Blocking version of `crate::Midgard::get_total_value_locked_history_range`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_total_value_locked_history_range`.
+	pub fn get_total_value_locked_history_range(&self, interval: Interval, range: Range<DateTime<Utc>>) -> Result<TVLHistory, MidgardError> {
+		self.runtime.block_on(self.inner.get_total_value_locked_history_range(interval, range))
+	}
+
+	/// Blocking version of `crate::Midgard::get_member_details`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_member_details`.
+	pub fn get_member_details(&self, address: &[String], show_savers: bool) -> Result<MemberDetails, MidgardError> {
+		self.runtime.block_on(self.inner.get_member_details(address, show_savers))
+	}
+
+	/// Blocking version of `crate::Midgard::get_member_list`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_member_list`.
+	pub fn get_member_list(&self, pool: Option<String>) -> Result<MemberList, MidgardError> {
+		self.runtime.block_on(self.inner.get_member_list(pool))
+	}
+
+	/// Blocking version of `crate::Midgard::get_network_data`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_network_data`.
+	pub fn get_network_data(&self) -> Result<NetworkData, MidgardError> {
+		self.runtime.block_on(self.inner.get_network_data())
+	}
+
+	/// Blocking version of `crate::Midgard::get_node_list`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_node_list`.
+	pub fn get_node_list(&self) -> Result<NodeList, MidgardError> {
+		self.runtime.block_on(self.inner.get_node_list())
+	}
+
+	/// Blocking version of `crate::Midgard::get_pool_list`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_pool_list`.
+	pub fn get_pool_list(&self, status: Option<PoolStatus>, period: Option<TimePeriod>) -> Result<PoolList, MidgardError> {
+		self.runtime.block_on(self.inner.get_pool_list(status, period))
+	}
+
+	/// Blocking version of `crate::Midgard::get_details_of_pool`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_details_of_pool`.
+	pub fn get_details_of_pool(&self, pool: &str, period: Option<TimePeriod>) -> Result<Pool, MidgardError> {
+		self.runtime.block_on(self.inner.get_details_of_pool(pool, period))
+	}
+
+	/// Blocking version of `crate::Midgard::get_known_pool_list`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_known_pool_list`.
+	pub fn get_known_pool_list(&self) -> Result<KnownPoolList, MidgardError> {
+		self.runtime.block_on(self.inner.get_known_pool_list())
+	}
+
+	/// Blocking version of `crate::Midgard::get_statistics_of_pool`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_statistics_of_pool`.
+	pub fn get_statistics_of_pool(&self, pool: &str, period: Option<TimePeriod>) -> Result<PoolStatistics, MidgardError> {
+		self.runtime.block_on(self.inner.get_statistics_of_pool(pool, period))
+	}
+
+	/// Blocking version of `crate::Midgard::get_savers_details`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_savers_details`.
+	pub fn get_savers_details(&self, address: &[String]) -> Result<SaversDetails, MidgardError> {
+		self.runtime.block_on(self.inner.get_savers_details(address))
+	}
+
+	/// Blocking version of `crate::Midgard::get_thorname_details`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_thorname_details`.
+	pub fn get_thorname_details(&self, name: &str) -> Result<ThornameDetails, MidgardError> {
+		self.runtime.block_on(self.inner.get_thorname_details(name))
+	}
+
+	/// Blocking version of `crate::Midgard::get_thorname_owner`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_thorname_owner`.
+	pub fn get_thorname_owner(&self, address: &str) -> Result<ThornameOwner, MidgardError> {
+		self.runtime.block_on(self.inner.get_thorname_owner(address))
+	}
+
+	/// Blocking version of `crate::Midgard::get_thorname_reverse_lookup`.
+	///
+	/// # Errors
+	/// The errors of `crate::Midgard::get_thorname_reverse_lookup`.
+	pub fn get_thorname_reverse_lookup(&self, address: &str) -> Result<ThornameReverseLookup, MidgardError> {
+		self.runtime.block_on(self.inner.get_thorname_reverse_lookup(address))
+	}
+}
+
+impl Default for Midgard {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_support::MockMidgard;
+
+	#[test]
+	fn test_blocking_calls() {
+		let server_runtime = Runtime::new().unwrap();
+		let server = server_runtime.block_on(MockMidgard::start());
+		server.set_response("health", 200, r#"{"inSync":true,"scannerHeight":"100","lastCommitted":{"height":100,"timestamp":0}}"#);
+
+		let midgard = Midgard::with_config(server.get_config());
+		let health = midgard.get_health_info().unwrap();
+
+		assert_eq!(health.get_in_sync(), Some(true));
+		assert_eq!(midgard.clone().get_metrics().get_total().get_requests(), 1);
+	}
+}
@@ -1,5 +1,7 @@
 #![warn(clippy::pedantic, clippy::nursery, clippy::all, clippy::cargo)]
 #![allow(clippy::multiple_crate_versions)]
+// browser futures are bound to the thread they were created on
+#![cfg_attr(target_arch = "wasm32", allow(clippy::future_not_send))]
 
 //! # Midgard
 //! Consumer information relating to swaps, pools, and volume. Midgard returns time-series information regarding the `THORChain` network, such as volume, pool information, users, liquidity providers and more. It also proxies to `THORNode` to reduce burden on the network. Runs on every node.
@@ -14,7 +16,9 @@
 //! ```rust
 //! use midgard_rs::Midgard;
 //! # tokio_test::block_on(async {
-//! let mut midgard = Midgard::new();
+//! let midgard = Midgard::new();
+//! # let server = midgard_rs::test_support::MockMidgard::start().await;
+//! # let midgard = server.get_midgard();
 //! let address = "thor102y0m3uptg0vvudeyh00r2fnz70wq7d8y7mu2g";
 //! let balance = midgard.get_balance(address, None, None).await.unwrap();
 //! assert!(*balance.get_height() > 0);
@@ -28,20 +32,318 @@
 //! use midgard_rs::Configuration;
 //! # tokio_test::block_on(async {
 //! let config = Configuration::new("https://midgard.ninerealms.com/v2/".to_string(), 1000); // base_url, rate_limit_ms
-//! let mut midgard = Midgard::with_config(config);
+//! let midgard = Midgard::with_config(config);
+//! # let server = midgard_rs::test_support::MockMidgard::start().await;
+//! # let midgard = server.get_midgard();
 //! let address = "thor102y0m3uptg0vvudeyh00r2fnz70wq7d8y7mu2g";
 //! let balance = midgard.get_balance(address, None, None).await.unwrap(); // address, timestamp, height
 //! assert!(*balance.get_height() > 0);
 //! # });
 //! ```
 //! 
+//! ## Custom HTTP Client
+//! 
+//! Every request is sent through a single `reqwest::Client` owned by `Midgard`, so connections are pooled and kept alive between calls.
+//! A custom client can be supplied to apply proxy settings, TLS roots, default headers or timeouts to every endpoint.
+//! 
+//! ```rust
+//! use midgard_rs::Midgard;
+//! use midgard_rs::Configuration;
+//! # tokio_test::block_on(async {
+//! let client = reqwest::Client::builder().timeout(std::time::Duration::from_secs(30)).build().unwrap();
+//! let midgard = Midgard::with_client(Configuration::default(), client);
+//! # let server = midgard_rs::test_support::MockMidgard::start().await;
+//! # let midgard = server.get_midgard();
+//! let address = "thor102y0m3uptg0vvudeyh00r2fnz70wq7d8y7mu2g";
+//! let balance = midgard.get_balance(address, None, None).await.unwrap();
+//! assert!(*balance.get_height() > 0);
+//! # });
+//! ```
+//! 
+//! ## Custom Transport
+//! 
+//! Requests are sent through a `Transport`, `ReqwestTransport` by default. Implementing the trait runs the client over any HTTP backend,
+//! a test double or an in-process service; it receives the method, url, headers and read timeout of a request and returns the status, headers and body.
+//! 
+//! ```rust
+//! use std::sync::Arc;
+//! 
+//! use midgard_rs::{Configuration, Midgard, ReqwestTransport};
+//! 
+//! let midgard = Midgard::with_transport(Configuration::default(), Arc::new(ReqwestTransport::default()));
+//! ```
+//! 
+//! ## Blocking Client
+//! 
+//! With the `blocking` feature, `blocking::Midgard` offers the same `get_*` methods without `async`, for scripts and CLI tools.
+//! It runs the requests on its own runtime, so it must not be used from within an async runtime.
+//! 
+//! ```toml
+//! midgard-rs = { version = "0.0.5", features = ["blocking"] }
+//! ```
+//! 
+//! ## WebAssembly
+//! 
+//! The crate runs on `tokio` by default. For `wasm32-unknown-unknown`, disable the default features and enable `wasm`:
+//! requests go through the browser fetch API, and sleeping, timeouts and failover probes use the browser timers.
+//! 
+//! ```toml
+//! midgard-rs = { version = "0.0.5", default-features = false, features = ["wasm"] }
+//! ```
+//! 
+//! Futures are not `Send` on wasm32, and browsers have no file system: a configured history store or cassette fails with an `Unsupported` IO error.
+//! 
+//! ## Types Only
+//! 
+//! The response models (`Pool`, `Action`, `SwapInterval`, ...) can be used without the client, to deserialize Midgard JSON received from queues or caches.
+//! Without default features the crate depends on `serde`, `chrono` and `rust_decimal`, plus `serde-aux` and `serde_with`, which the models use to read Midgard's numbers and timestamps sent as strings.
+//! No HTTP client or async runtime is pulled in: `Midgard`, `MidgardError` and `HistoryQuery` come with the `client` feature, which the `tokio` and `wasm` features enable.
+//! 
+//! ```toml
+//! midgard-rs = { version = "0.0.5", default-features = false }
+//! ```
+//! 
+//! ```rust
+//! use midgard_rs::Pool;
+//! 
+//! # let json = midgard_rs::test_support::fixture("pools").unwrap();
+//! let pools: Vec<Pool> = serde_json::from_str(json).unwrap();
+//! assert!(!pools.is_empty());
+//! ```
+//! 
+//! ## Headers and Authentication
+//! 
+//! Default headers and credentials set on the `Configuration` are sent with every request. Secrets are redacted in `Debug` output and when the configuration is serialized.
+//! 
+//! ```rust
+//! use midgard_rs::{Auth, Configuration, Midgard, Secret};
+//! 
+//! let mut config = Configuration::new("https://midgard.example.com/v2/".to_string(), 100);
+//! config.set_user_agent("my-app/1.0");
+//! config.set_header("x-client-id", "my-app");
+//! config.set_auth(Some(Auth::Bearer { token: Secret::new("token") }));
+//! let midgard = Midgard::with_config(config);
+//! ```
+//! 
+//! ## Rate Limits
+//! 
+//! Requests are rate limited by a token bucket shared by a `Midgard` instance and all of its clones: a sustained rate of one request per `rate_limit_ms` and a configurable burst.
+//! Groups of endpoints can be given stricter limits, and the limits are lowered automatically when Midgard answers with `429 Too Many Requests`.
+//! 
+//! ```rust
+//! use midgard_rs::{Configuration, EndpointGroup, Midgard, RateLimit};
+//! 
+//! let mut config = Configuration::default();
+//! config.set_rate_limit(RateLimit::new(200, 10)); // interval_ms, burst
+//! config.set_group_rate_limit(EndpointGroup::History, RateLimit::new(1_000, 2));
+//! let midgard = Midgard::with_config(config);
+//! ```
+//! 
+//! ## Retries
+//! 
+//! Requests failing with a timeout, a connection error or a `429`/`502`/`503`/`504` status are retried up to 3 times with exponential backoff and jitter. `Retry-After` headers are honored.
+//! The behaviour can be changed with a `RetryPolicy`.
+//! 
+//! ```rust
+//! use midgard_rs::{Configuration, Midgard, RetryPolicy};
+//! 
+//! let mut config = Configuration::default();
+//! config.set_retry_policy(RetryPolicy::new(5, 250, 30_000)); // max_attempts, base_delay_ms, max_delay_ms
+//! let midgard = Midgard::with_config(config);
+//! ```
+//! 
+//! ## Timeouts
+//! 
+//! Requests time out after 10 seconds without a connection, 30 seconds without data or 60 seconds in total, failing with `MidgardError::Timeout`.
+//! The limits are set with `Timeouts` on the `Configuration`, and can be overridden for single calls with `Midgard::with_timeouts`.
+//! Dropping the future of a call cancels its request; a slot it reserved from the rate limiter is given back.
+//! 
+//! ```rust
+//! use midgard_rs::{Configuration, Midgard, Timeouts};
+//! 
+//! let mut config = Configuration::default();
+//! config.set_timeouts(Timeouts::new(Some(5_000), Some(10_000), Some(20_000))); // connect_ms, read_ms, total_ms
+//! let midgard = Midgard::with_config(config);
+//! let patient = midgard.with_timeouts(Timeouts::none());
+//! ```
+//! 
+//! ## Failover
+//! 
+//! Several base urls can be configured. Requests go to the first healthy one and fail over to the next when an instance can't be reached or answers with a 5xx status.
+//! All instances are probed through `/health` in the background and skipped while they are out of sync or their scanner lags behind.
+//! With `Routing::HighestCommitted` requests go to the instance with the highest committed height instead, and consecutive reads never go back in height.
+//! 
+//! ```rust
+//! use midgard_rs::{Configuration, FailoverPolicy, Midgard};
+//! 
+//! let mut config = Configuration::default();
+//! config.set_fallback_base_urls(vec!["https://midgard.thorchain.liquify.com/v2/".to_string()]);
+//! config.set_failover_policy(FailoverPolicy::new(10_000, 50)); // probe_interval_ms, max_scanner_lag
+//! let midgard = Midgard::with_config(config);
+//! ```
+//! 
+//! ## Caching
+//! 
+//! An optional in-memory cache keyed on the endpoint serves repeated calls without waiting for the rate limiter. TTLs are set per endpoint group,
+//! history responses whose intervals have all ended are kept indefinitely. `Midgard::bypass_cache` and `Midgard::invalidate_cache` skip or drop cached responses.
+//! 
+//! ```rust
+//! use midgard_rs::{CachePolicy, Configuration, EndpointGroup, Midgard};
+//! 
+//! let mut policy = CachePolicy::new(true);
+//! policy.set_ttl_ms(EndpointGroup::Pools, 60_000);
+//! let mut config = Configuration::default();
+//! config.set_cache_policy(policy);
+//! let midgard = Midgard::with_config(config);
+//! midgard.invalidate_cache(EndpointGroup::Pools);
+//! ```
+//! 
+//! ## History Ranges
+//! 
+//! Midgard returns at most 400 intervals per history request. The `_range` history methods take a `from..to` range of any length and fetch it page by page,
+//! each request waiting for the rate limiter. The pages are merged into a single response: intervals are concatenated in order and the meta is recomputed over the whole range.
+//! 
+//! ```rust
+//! use chrono::{Duration, Utc};
+//! use midgard_rs::{Interval, Midgard};
+//! 
+//! # tokio_test::block_on(async {
+//! let midgard = Midgard::new();
+//! # let server = midgard_rs::test_support::MockMidgard::start().await;
+//! # let midgard = server.get_midgard();
+//! let now = Utc::now();
+//! let history = midgard.get_swaps_history_range(None, Interval::Hour, now - Duration::days(365)..now).await.unwrap();
+//! # });
+//! ```
+//! 
+//! ## History Store
+//! 
+//! The `_range` history methods can also keep closed intervals on disk, keyed by endpoint, pool, interval and start time.
+//! Later calls then only request the windows that are not stored yet, so a backfill can be rerun offline.
+//! 
+//! ```rust
+//! use midgard_rs::{Configuration, Midgard};
+//! 
+//! let mut config = Configuration::default();
+//! config.set_history_store_dir(Some("./midgard-history".into()));
+//! let midgard = Midgard::with_config(config);
+//! ```
+//! 
+//! ## Middleware
+//! 
+//! `Middleware` hooks run before every request is sent and after its response is read, seeing the method, url, status, latency and body size.
+//! `LoggingMiddleware` logs requests through the `log` crate and `TimingMiddleware` collects latencies per endpoint group.
+//! 
+//! ```rust
+//! use std::sync::Arc;
+//! 
+//! use midgard_rs::{LoggingMiddleware, Midgard, TimingMiddleware};
+//! 
+//! let timing = Arc::new(TimingMiddleware::new());
+//! let mut midgard = Midgard::new();
+//! midgard.add_middleware(Arc::new(LoggingMiddleware));
+//! midgard.add_middleware(timing.clone());
+//! ```
+//! 
+//! ## Tracing
+//! 
+//! With the `tracing` feature every `Midgard::get_*` call runs in an `info` span named after the method, carrying its key parameters (pool, interval, count, ...).
+//! The span records the endpoint, whether the response came from the cache, the number of attempts and the time spent waiting for the rate limiter in `rate_limit_wait_ms`.
+//! Events are emitted for retries, failovers, 429 responses, cache and history store hits, and responses that fail to parse.
+//! 
+//! ```toml
+//! midgard-rs = { version = "0.0.5", features = ["tracing"] }
+//! ```
+//! 
+//! ## Metrics
+//! 
+//! Every `Midgard` instance counts requests, errors by kind, retries and cache hits per endpoint path, and keeps histograms of the request latencies and of the time spent waiting for the rate limiter.
+//! `Midgard::get_metrics` returns a snapshot shared by the instance and its clones, from which single endpoints or whole endpoint groups can be read.
+//! With the `metrics` feature the same measurements are exported through the `metrics` crate as `midgard_requests_total`, `midgard_errors_total`, `midgard_retries_total`, `midgard_cache_hits_total`, `midgard_request_duration_seconds` and `midgard_rate_limit_wait_seconds`, labelled by `group` (and `kind` for errors).
+//! 
+//! ```rust
+//! use midgard_rs::{EndpointGroup, Midgard};
+//! 
+//! let midgard = Midgard::new();
+//! 
+//! // ... make some calls
+//! 
+//! let pools = midgard.get_metrics().get_group(EndpointGroup::Pools);
+//! println!("{} requests, {} errors, {:?} waiting for the rate limiter", pools.get_requests(), pools.get_error_count(), pools.get_rate_limit_wait().get_sum());
+//! ```
+//! 
+//! ## Testing
+//! 
+//! With the `testing` feature, `test_support::MockMidgard` starts a local HTTP server that answers every endpoint with a recorded response, so tests run offline and deterministically.
+//! Single endpoints can be made to return other bodies or error statuses with `MockMidgard::set_response`.
+//! 
+//! ```toml
+//! [dev-dependencies]
+//! midgard-rs = { version = "0.0.5", features = ["testing"] }
+//! ```
+//! 
+//! ```rust
+//! use midgard_rs::test_support::MockMidgard;
+//! # tokio_test::block_on(async {
+//! let server = MockMidgard::start().await;
+//! server.set_response("health", 503, r#"{"error":"maintenance"}"#);
+//! let midgard = server.get_midgard(); // or Midgard::with_config(server.get_config())
+//! 
+//! let node_list = midgard.get_node_list().await.unwrap();
+//! assert!(!node_list.get_nodes().is_empty());
+//! # });
+//! ```
+//! 
+//! ## Record and Replay
+//! 
+//! A `Cassette` captures real Midgard traffic for regression tests. In record mode the final response of each call, after retries and failover, is written to a JSON file together with its request url;
+//! in replay mode the responses are served from that file without touching the network. Re-recording a cassette shows schema changes as a diff and as parse failures on replay.
+//! 
+//! ```rust
+//! use midgard_rs::{Cassette, Configuration, Midgard};
+//! 
+//! let mut config = Configuration::default();
+//! config.set_cassette(Some(Cassette::replay("tests/cassettes/pools.json"))); // or Cassette::record(...)
+//! let midgard = Midgard::with_config(config);
+//! ```
+//! 
+//! ## Errors
+//! 
+//! Every method returns a `MidgardError`, which carries the endpoint, the HTTP status, a snippet of the response body and, for parse failures, the serde path of the field that failed.
+//! 
+//! ```rust
+//! use midgard_rs::{Midgard, MidgardError};
+//! # tokio_test::block_on(async {
+//! let midgard = Midgard::new();
+//! # let server = midgard_rs::test_support::MockMidgard::start().await;
+//! # let midgard = server.get_midgard();
+//! match midgard.get_details_of_pool("NOT.A.POOL", None).await {
+//!     Ok(pool) => println!("{}", pool.get_asset()),
+//!     Err(MidgardError::Timeout { endpoint }) => eprintln!("{endpoint} timed out"),
+//!     Err(e) => eprintln!("status: {:?}, endpoint: {:?}", e.status(), e.endpoint()),
+//! }
+//! # });
+//! ```
+//! 
 
+#[cfg(feature = "client")]
 pub(crate) use api::{api_get_action_list, api_get_balance, api_get_borrowers_details, api_get_borrowers_list, api_get_churn_list, api_get_depth_and_price_history, api_get_details_of_pool, api_get_earnings_history, api_get_global_stats, api_get_health_info, api_get_known_pool_list, api_get_liquidity_change_history, api_get_member_details, api_get_member_list, api_get_network_data, api_get_node_list, api_get_pool_list, api_get_savers_details, api_get_savers_units_and_depth_history, api_get_statistics_of_pool, api_get_swaps_history, api_get_thorname_details, api_get_thorname_owner, api_get_thorname_reverse_lookup, api_get_total_value_locked_history};
+#[cfg(feature = "client")]
 pub use midgard::*;
 pub use types::*;
 
+#[cfg(feature = "client")]
 mod api;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "client")]
 mod midgard;
+#[cfg(feature = "client")]
+mod runtime;
+#[cfg(any(all(test, feature = "tokio"), feature = "testing"))]
+pub mod test_support;
+#[cfg(feature = "client")]
+mod trace;
 mod types;
 
 
@@ -0,0 +1,119 @@
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A credential that never shows up in `Debug` output or serialized configurations.
+///
+/// Serializing a `Secret` writes a redacted placeholder, so a configuration saved to disk or logged as JSON has to be given its secrets again after loading.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+	/// Placeholder written instead of the secret.
+	pub const REDACTED: &'static str = "[redacted]";
+
+	#[must_use]
+	pub fn new(secret: impl Into<String>) -> Self {
+		Self(secret.into())
+	}
+
+	/// Returns the secret itself.
+	#[must_use]
+	pub fn expose(&self) -> &str {
+		&self.0
+	}
+}
+
+impl fmt::Debug for Secret {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(Self::REDACTED)
+	}
+}
+
+impl Serialize for Secret {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(Self::REDACTED)
+	}
+}
+
+impl<'de> Deserialize<'de> for Secret {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		String::deserialize(deserializer).map(Self)
+	}
+}
+
+/// Supplies authentication headers for every request.
+///
+/// Implement it for credentials that change over time, e.g. tokens that have to be refreshed, and set it with `Configuration::set_auth_provider`.
+/// `headers` is called once per request attempt.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+///
+/// use midgard_rs::{AuthProvider, Configuration, Secret};
+///
+/// #[derive(Debug)]
+/// struct GatewayToken;
+///
+/// impl AuthProvider for GatewayToken {
+///     fn headers(&self) -> Vec<(String, Secret)> {
+///         vec![("x-gateway-token".to_string(), Secret::new(std::env::var("GATEWAY_TOKEN").unwrap_or_default()))]
+///     }
+/// }
+///
+/// let mut config = Configuration::default();
+/// config.set_auth_provider(Some(Arc::new(GatewayToken)));
+/// ```
+pub trait AuthProvider: fmt::Debug + Send + Sync {
+	/// Returns the header names and values to add to a request.
+	fn headers(&self) -> Vec<(String, Secret)>;
+}
+
+/// Static credentials sent with every request.
+///
+/// # Example
+///
+/// ```rust
+/// use midgard_rs::{Auth, Configuration, Secret};
+///
+/// let mut config = Configuration::new("https://midgard.example.com/v2/".to_string(), 100);
+/// config.set_auth(Some(Auth::ApiKey { header: "x-client-id".to_string(), key: Secret::new("my-client-id") }));
+///
+/// assert!(!format!("{config:?}").contains("my-client-id"));
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Auth {
+	/// `Authorization: Bearer <token>`
+	Bearer { token: Secret },
+	/// A key sent in a custom header such as `x-api-key` or `x-client-id`.
+	ApiKey { header: String, key: Secret },
+}
+
+impl AuthProvider for Auth {
+	fn headers(&self) -> Vec<(String, Secret)> {
+		match self {
+			Self::Bearer { token } => vec![("Authorization".to_string(), Secret::new(format!("Bearer {}", token.expose())))],
+			Self::ApiKey { header, key } => vec![(header.clone(), key.clone())],
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_secrets_are_redacted() {
+		let auth = Auth::Bearer { token: Secret::new("hunter2") };
+
+		assert!(!format!("{auth:?}").contains("hunter2"));
+		assert!(!serde_json::to_string(&auth).unwrap().contains("hunter2"));
+		assert_eq!(auth.headers()[0].1.expose(), "Bearer hunter2");
+
+		let auth: Auth = serde_json::from_str(r#"{"api_key":{"header":"x-client-id","key":"abc"}}"#).unwrap();
+		assert_eq!(auth.headers(), vec![("x-client-id".to_string(), Secret::new("abc"))]);
+	}
+}
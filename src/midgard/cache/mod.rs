@@ -0,0 +1,7 @@
+//! Response caching: the configured `CachePolicy` and the in-memory store applying it.
+
+pub use policy::CachePolicy;
+pub use store::{is_closed_history, ResponseCache};
+
+mod policy;
+mod store;
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::EndpointGroup;
+
+/// Controls the optional in-memory response cache.
+///
+/// Responses are cached by endpoint (path and query) under the primary base url for the TTL of their endpoint group; endpoints of groups without a TTL are not cached.
+/// The key doesn't depend on the instance that answered after failover or on urls rewritten by middleware, so a cached response is found whichever instance served it.
+/// History responses whose last interval has already ended never change, with `cache_closed_history` they are kept until they are invalidated or evicted.
+/// Once `max_entries` responses are cached, expired entries and then the ones expiring first make room for new ones.
+///
+/// # Example
+///
+/// ```rust
+/// use midgard_rs::{CachePolicy, Configuration, EndpointGroup, Midgard};
+///
+/// let mut policy = CachePolicy::new(true);
+/// policy.set_ttl_ms(EndpointGroup::Pools, 60_000);
+/// policy.set_ttl_ms(EndpointGroup::Network, 10_000);
+///
+/// let mut config = Configuration::default();
+/// config.set_cache_policy(policy);
+/// let midgard = Midgard::with_config(config);
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct CachePolicy {
+	enabled: bool,
+	/// Time to live of cached responses per endpoint group.
+	ttls_ms: HashMap<EndpointGroup, u64>,
+	/// Whether history responses that only contain closed intervals are cached indefinitely.
+	cache_closed_history: bool,
+	max_entries: usize,
+}
+
+impl CachePolicy {
+	/// Creates a policy with the default TTLs: 30 seconds for the `Pools` and `Network` groups.
+	#[must_use]
+	pub fn new(enabled: bool) -> Self {
+		Self { enabled, ..Self::default() }
+	}
+
+	#[must_use]
+	pub const fn get_enabled(&self) -> bool {
+		self.enabled
+	}
+
+	/// Returns the TTL of an endpoint group, if its responses are cached.
+	#[must_use]
+	pub fn get_ttl_ms(&self, group: EndpointGroup) -> Option<u64> {
+		self.ttls_ms.get(&group).copied()
+	}
+
+	#[must_use]
+	pub const fn get_ttls_ms(&self) -> &HashMap<EndpointGroup, u64> {
+		&self.ttls_ms
+	}
+
+	#[must_use]
+	pub const fn get_cache_closed_history(&self) -> bool {
+		self.cache_closed_history
+	}
+
+	#[must_use]
+	pub const fn get_max_entries(&self) -> usize {
+		self.max_entries
+	}
+
+	pub const fn set_enabled(&mut self, enabled: bool) {
+		self.enabled = enabled;
+	}
+
+	pub fn set_ttl_ms(&mut self, group: EndpointGroup, ttl_ms: u64) {
+		self.ttls_ms.insert(group, ttl_ms);
+	}
+
+	/// Stops caching the responses of an endpoint group, except for closed history intervals.
+	pub fn remove_ttl(&mut self, group: EndpointGroup) {
+		self.ttls_ms.remove(&group);
+	}
+
+	pub const fn set_cache_closed_history(&mut self, cache_closed_history: bool) {
+		self.cache_closed_history = cache_closed_history;
+	}
+
+	pub const fn set_max_entries(&mut self, max_entries: usize) {
+		self.max_entries = max_entries;
+	}
+}
+
+impl Default for CachePolicy {
+	fn default() -> Self {
+		Self { enabled: false, ttls_ms: HashMap::from([(EndpointGroup::Pools, 30_000), (EndpointGroup::Network, 30_000)]), cache_closed_history: true, max_entries: 1_000 }
+	}
+}
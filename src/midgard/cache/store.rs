@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_aux::prelude::*;
+
+use crate::runtime::Instant;
+use crate::EndpointGroup;
+
+#[derive(Debug, Clone)]
+struct Entry {
+	group: Option<EndpointGroup>,
+	status: u16,
+	body: String,
+	/// `None` for responses that never change.
+	expires_at: Option<Instant>,
+}
+
+impl Entry {
+	fn is_expired(&self, now: Instant) -> bool {
+		self.expires_at.is_some_and(|expires_at| expires_at <= now)
+	}
+}
+
+/// Cached responses keyed by primary base url and endpoint, shared by a `Midgard` instance and all of its clones.
+#[derive(Debug, Clone, Default)]
+pub struct ResponseCache {
+	entries: Arc<Mutex<HashMap<String, Entry>>>,
+}
+
+impl ResponseCache {
+	/// Returns the status and body cached for `key`, unless the entry has expired.
+	pub fn get(&self, key: &str, now: Instant) -> Option<(u16, String)> {
+		self.lock().get(key).filter(|entry| !entry.is_expired(now)).map(|entry| (entry.status, entry.body.clone()))
+	}
+
+	/// Caches a response until `expires_at`, or indefinitely if it is `None`. Makes room first if `max_entries` are cached already.
+	pub fn insert(&self, key: String, group: Option<EndpointGroup>, (status, body): (u16, String), expires_at: Option<Instant>, max_entries: usize, now: Instant) {
+		if max_entries == 0 {
+			return;
+		}
+		let mut entries = self.lock();
+		if entries.len() >= max_entries && !entries.contains_key(&key) {
+			entries.retain(|_, entry| !entry.is_expired(now));
+		}
+		while entries.len() >= max_entries && !entries.contains_key(&key) {
+			let Some(first_to_expire) = entries.iter().min_by_key(|(_, entry)| (entry.expires_at.is_none(), entry.expires_at)).map(|(key, _)| key.clone()) else {
+				break;
+			};
+			entries.remove(&first_to_expire);
+		}
+		entries.insert(key, Entry { group, status, body, expires_at });
+	}
+
+	pub fn clear(&self) {
+		self.lock().clear();
+	}
+
+	pub fn invalidate_group(&self, group: EndpointGroup) {
+		self.lock().retain(|_, entry| entry.group != Some(group));
+	}
+
+	fn lock(&self) -> MutexGuard<'_, HashMap<String, Entry>> {
+		self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+}
+
+/// Returns true if `body` is a history response whose last interval ended before `now`, so it can't change anymore.
+pub fn is_closed_history(body: &str, now: DateTime<Utc>) -> bool {
+	#[derive(Deserialize)]
+	struct Meta {
+		#[serde(rename = "endTime", deserialize_with = "deserialize_number_from_string")]
+		end_time: i64,
+	}
+	#[derive(Deserialize)]
+	struct History {
+		meta: Meta,
+	}
+
+	serde_json::from_str::<History>(body).is_ok_and(|history| history.meta.end_time <= now.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use super::*;
+
+	#[test]
+	fn test_entries_expire() {
+		let cache = ResponseCache::default();
+		let now = Instant::now();
+
+		cache.insert("https://a/v2/pools".to_string(), Some(EndpointGroup::Pools), (200, "[]".to_string()), Some(now + Duration::from_secs(30)), 10, now);
+
+		assert_eq!(cache.get("https://a/v2/pools", now), Some((200, "[]".to_string())));
+		assert_eq!(cache.get("https://a/v2/pools", now + Duration::from_secs(30)), None);
+		assert_eq!(cache.get("https://a/v2/network", now), None);
+	}
+
+	#[test]
+	fn test_insert_evicts_entries_expiring_first() {
+		let cache = ResponseCache::default();
+		let now = Instant::now();
+		let response = || (200, "{}".to_string());
+
+		cache.insert("closed".to_string(), Some(EndpointGroup::History), response(), None, 2, now);
+		cache.insert("soon".to_string(), Some(EndpointGroup::Pools), response(), Some(now + Duration::from_secs(1)), 2, now);
+		cache.insert("later".to_string(), Some(EndpointGroup::Network), response(), Some(now + Duration::from_secs(45)), 2, now);
+
+		assert!(cache.get("closed", now).is_some());
+		assert!(cache.get("soon", now).is_none());
+		assert!(cache.get("later", now).is_some());
+
+		cache.invalidate_group(EndpointGroup::History);
+		assert!(cache.get("closed", now).is_none());
+	}
+
+	#[test]
+	fn test_is_closed_history() {
+		let now = DateTime::from_timestamp(1_710_892_800, 0).unwrap();
+
+		assert!(is_closed_history(r#"{"intervals":[],"meta":{"startTime":"1710806400","endTime":"1710892800"}}"#, now));
+		assert!(!is_closed_history(r#"{"intervals":[],"meta":{"startTime":"1710806400","endTime":"1710979200"}}"#, now));
+		assert!(!is_closed_history(r#"{"pools":[]}"#, now));
+	}
+}
@@ -0,0 +1,182 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use futures::lock::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::runtime;
+use crate::MidgardError;
+
+/// Whether a cassette captures real traffic or stands in for it.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum CassetteMode {
+	/// Requests are sent as usual and the final response of each call, after retries and failover, is written to the cassette. An existing cassette is replaced on the first response.
+	Record,
+	/// No request is sent: responses are served from the cassette, failing with `MidgardError::Cassette` for requests it doesn't contain.
+	Replay,
+}
+
+/// A JSON file of recorded Midgard responses, set with `Configuration::set_cassette`.
+///
+/// Each recorded response is stored with the url and the endpoint (path and query relative to the base url) it was requested from.
+/// Replay matches requests on the endpoint, so a cassette recorded against one instance can be replayed with any base url.
+/// An endpoint recorded several times is replayed in the recorded order, the last response repeating once the others have been served.
+/// Replayed requests skip the rate limiter and failover; middleware, retries, the response cache and metrics work as usual.
+///
+/// # Example
+///
+/// ```rust
+/// use midgard_rs::{Cassette, Configuration, Midgard};
+///
+/// // capture the responses once
+/// let mut config = Configuration::default();
+/// config.set_cassette(Some(Cassette::record("tests/cassettes/pools.json")));
+///
+/// // and serve them without network afterwards
+/// config.set_cassette(Some(Cassette::replay("tests/cassettes/pools.json")));
+/// let midgard = Midgard::with_config(config);
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Cassette {
+	path: PathBuf,
+	mode: CassetteMode,
+}
+
+impl Cassette {
+	#[must_use]
+	pub fn new(path: impl Into<PathBuf>, mode: CassetteMode) -> Self {
+		Self { path: path.into(), mode }
+	}
+
+	#[must_use]
+	pub fn record(path: impl Into<PathBuf>) -> Self {
+		Self::new(path, CassetteMode::Record)
+	}
+
+	#[must_use]
+	pub fn replay(path: impl Into<PathBuf>) -> Self {
+		Self::new(path, CassetteMode::Replay)
+	}
+
+	#[must_use]
+	pub fn get_path(&self) -> &Path {
+		&self.path
+	}
+
+	#[must_use]
+	pub const fn get_mode(&self) -> CassetteMode {
+		self.mode
+	}
+}
+
+/// A recorded response.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+struct Interaction {
+	endpoint: String,
+	url: String,
+	status: u16,
+	body: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct CassetteFile {
+	interactions: Vec<Interaction>,
+}
+
+/// A cassette loaded into memory.
+#[derive(Debug, Default)]
+struct Tape {
+	interactions: Vec<Interaction>,
+	/// Number of responses replayed per endpoint.
+	played: HashMap<String, usize>,
+}
+
+/// The cassettes in use by a `Midgard` instance and its clones, loaded on first use and keyed by path.
+#[derive(Debug, Clone, Default)]
+pub struct Tapes {
+	tapes: Arc<Mutex<HashMap<PathBuf, Tape>>>,
+}
+
+impl Tapes {
+	/// Appends a response to the cassette at `path` and writes the cassette back to disk.
+	pub async fn record(&self, path: &Path, endpoint: &str, url: &str, status: u16, body: &str) -> Result<(), MidgardError> {
+		let mut tapes = self.tapes.lock().await;
+		let tape = tapes.entry(path.to_path_buf()).or_default();
+		tape.interactions.push(Interaction { endpoint: endpoint.to_string(), url: url.to_string(), status, body: body.to_string() });
+		// the lock is held while writing, so concurrent recordings reach the file in order
+		let saved = save(path, &tape.interactions).await;
+		drop(tapes);
+		saved
+	}
+
+	/// Returns the next recorded status and body for `endpoint` from the cassette at `path`.
+	pub async fn replay(&self, path: &Path, endpoint: &str) -> Result<(u16, String), MidgardError> {
+		let mut tapes = self.tapes.lock().await;
+		let tape = match tapes.entry(path.to_path_buf()) {
+			Entry::Occupied(entry) => entry.into_mut(),
+			Entry::Vacant(entry) => entry.insert(Tape { interactions: load(path).await?, played: HashMap::new() }),
+		};
+
+		let recorded = tape.interactions.iter().filter(|interaction| interaction.endpoint == endpoint).collect::<Vec<_>>();
+		let played = tape.played.entry(endpoint.to_string()).or_default();
+		let response = recorded.get(*played).or_else(|| recorded.last()).map(|interaction| (interaction.status, interaction.body.clone()));
+		*played += 1;
+		drop(tapes);
+		response.ok_or_else(|| cassette_error(path, io::Error::new(io::ErrorKind::NotFound, format!("no response recorded for {endpoint}"))))
+	}
+}
+
+async fn load(path: &Path) -> Result<Vec<Interaction>, MidgardError> {
+	let bytes = runtime::read(path).await.map_err(|e| cassette_error(path, e))?;
+	let file: CassetteFile = serde_json::from_slice(&bytes).map_err(|e| cassette_error(path, io::Error::new(io::ErrorKind::InvalidData, e)))?;
+	Ok(file.interactions)
+}
+
+/// Writes to a temporary file first, so an interrupted write never leaves a truncated cassette behind.
+async fn save(path: &Path, interactions: &[Interaction]) -> Result<(), MidgardError> {
+	let bytes = serde_json::to_vec_pretty(&CassetteFile { interactions: interactions.to_vec() }).map_err(|e| cassette_error(path, io::Error::new(io::ErrorKind::InvalidData, e)))?;
+	let tmp = path.with_extension("json.tmp");
+	if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+		runtime::create_dir_all(dir).await.map_err(|e| cassette_error(dir, e))?;
+	}
+	runtime::write(&tmp, bytes).await.map_err(|e| cassette_error(&tmp, e))?;
+	runtime::rename(&tmp, path).await.map_err(|e| cassette_error(path, e))
+}
+
+fn cassette_error(path: &Path, source: io::Error) -> MidgardError {
+	MidgardError::Cassette { path: path.display().to_string(), source }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn test_records_and_replays_in_order() {
+		let path = std::env::temp_dir().join(format!("midgard-rs-cassette-{}.json", std::process::id()));
+		let tapes = Tapes::default();
+		tapes.record(&path, "health", "http://midgard/v2/health", 200, r#"{"inSync":false}"#).await.unwrap();
+		tapes.record(&path, "health", "http://midgard/v2/health", 200, r#"{"inSync":true}"#).await.unwrap();
+		tapes.record(&path, "pool/NOT.A.POOL", "http://midgard/v2/pool/NOT.A.POOL", 404, r#"{"error":"unknown pool"}"#).await.unwrap();
+
+		// a fresh instance reads the cassette from disk
+		let tapes = Tapes::default();
+		assert_eq!(tapes.replay(&path, "health").await.unwrap().1, r#"{"inSync":false}"#);
+		assert_eq!(tapes.replay(&path, "health").await.unwrap().1, r#"{"inSync":true}"#);
+		assert_eq!(tapes.replay(&path, "health").await.unwrap().1, r#"{"inSync":true}"#);
+		assert_eq!(tapes.replay(&path, "pool/NOT.A.POOL").await.unwrap().0, 404);
+		assert!(matches!(tapes.replay(&path, "nodes").await, Err(MidgardError::Cassette { .. })));
+
+		std::fs::remove_file(path).unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_missing_cassette() {
+		let error = Tapes::default().replay(Path::new("does-not-exist.json"), "health").await.unwrap_err();
+		assert_eq!(error.kind(), "cassette");
+	}
+}
@@ -1,15 +1,55 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
 
+use crate::{Auth, AuthProvider, CachePolicy, Cassette, EndpointGroup, FailoverPolicy, RateLimit, RetryPolicy, Timeouts};
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Configuration {
 	base_url: String,
+	/// Base urls used, in order, when `base_url` is unhealthy.
+	#[serde(default)]
+	fallback_base_urls: Vec<String>,
+	#[serde(default)]
+	failover_policy: FailoverPolicy,
+	/// Sustained rate of the global rate limit: one request per `rate_limit_ms`.
 	rate_limit_ms: u64,
+	/// Number of requests of the global rate limit that may be sent back to back.
+	#[serde(default = "default_rate_limit_burst")]
+	rate_limit_burst: u32,
+	/// Additional limits for groups of endpoints. A request has to satisfy both the global and its group's limit.
+	#[serde(default)]
+	group_rate_limits: HashMap<EndpointGroup, RateLimit>,
+	/// Whether the rate limits are lowered when Midgard answers with 429 Too Many Requests, and recover on later successful requests.
+	#[serde(default = "default_adaptive_rate_limit")]
+	adaptive_rate_limit: bool,
+	#[serde(default)]
+	retry_policy: RetryPolicy,
+	#[serde(default)]
+	timeouts: Timeouts,
+	#[serde(default)]
+	cache_policy: CachePolicy,
+	/// Directory in which closed history intervals are stored by the `_range` history methods.
+	#[serde(default)]
+	history_store_dir: Option<PathBuf>,
+	/// Cassette the final response of each call is recorded to, or responses are replayed from.
+	#[serde(default)]
+	cassette: Option<Cassette>,
+	/// Headers sent with every request, e.g. `User-Agent`.
+	#[serde(default)]
+	headers: BTreeMap<String, String>,
+	#[serde(default)]
+	auth: Option<Auth>,
+	#[serde(skip)]
+	auth_provider: Option<Arc<dyn AuthProvider>>,
 }
 
 impl Configuration {
         #[must_use]
-	pub const fn new(base_url: String, rate_limit_ms: u64) -> Self {
-		Self { base_url, rate_limit_ms }
+	pub fn new(base_url: String, rate_limit_ms: u64) -> Self {
+		Self { base_url, rate_limit_ms, ..Self::default() }
 	}
 
         #[must_use]
@@ -17,22 +57,235 @@ impl Configuration {
 		&self.base_url
 	}
 
+	#[must_use]
+	pub fn get_fallback_base_urls(&self) -> &[String] {
+		&self.fallback_base_urls
+	}
+
+	/// Returns all base urls in the order they are tried: `base_url` first, then the fallbacks.
+	#[must_use]
+	pub fn get_base_urls(&self) -> Vec<&str> {
+		std::iter::once(self.base_url.as_str()).chain(self.fallback_base_urls.iter().map(String::as_str)).collect()
+	}
+
+	#[must_use]
+	pub const fn get_failover_policy(&self) -> &FailoverPolicy {
+		&self.failover_policy
+	}
+
         #[must_use]
 	pub const fn get_rate_limit_ms(&self) -> u64 {
 		self.rate_limit_ms
 	}
 
+	/// Returns the global rate limit.
+	#[must_use]
+	pub const fn get_rate_limit(&self) -> RateLimit {
+		RateLimit::new(self.rate_limit_ms, self.rate_limit_burst)
+	}
+
+	/// Returns the additional rate limit of an endpoint group, if one is set.
+	#[must_use]
+	pub fn get_group_rate_limit(&self, group: EndpointGroup) -> Option<RateLimit> {
+		self.group_rate_limits.get(&group).copied()
+	}
+
+	#[must_use]
+	pub const fn get_group_rate_limits(&self) -> &HashMap<EndpointGroup, RateLimit> {
+		&self.group_rate_limits
+	}
+
+	#[must_use]
+	pub const fn get_adaptive_rate_limit(&self) -> bool {
+		self.adaptive_rate_limit
+	}
+
+	#[must_use]
+	pub const fn get_retry_policy(&self) -> &RetryPolicy {
+		&self.retry_policy
+	}
+
+	#[must_use]
+	pub const fn get_timeouts(&self) -> &Timeouts {
+		&self.timeouts
+	}
+
+	#[must_use]
+	pub const fn get_cache_policy(&self) -> &CachePolicy {
+		&self.cache_policy
+	}
+
+	#[must_use]
+	pub const fn get_headers(&self) -> &BTreeMap<String, String> {
+		&self.headers
+	}
+
+	#[must_use]
+	pub const fn get_auth(&self) -> Option<&Auth> {
+		self.auth.as_ref()
+	}
+
+	#[must_use]
+	pub fn get_auth_provider(&self) -> Option<&Arc<dyn AuthProvider>> {
+		self.auth_provider.as_ref()
+	}
+
+	#[must_use]
+	pub fn get_history_store_dir(&self) -> Option<&Path> {
+		self.history_store_dir.as_deref()
+	}
+
+	#[must_use]
+	pub const fn get_cassette(&self) -> Option<&Cassette> {
+		self.cassette.as_ref()
+	}
+
 	pub fn set_base_url(&mut self, base_url: String) {
 		self.base_url = base_url;
 	}
 
+	pub fn set_fallback_base_urls(&mut self, fallback_base_urls: Vec<String>) {
+		self.fallback_base_urls = fallback_base_urls;
+	}
+
+	/// Sets an ordered list of base urls. The first one becomes `base_url`, the others its fallbacks. An empty list is ignored.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use midgard_rs::Configuration;
+	///
+	/// let mut config = Configuration::default();
+	/// config.set_base_urls(vec!["https://midgard.ninerealms.com/v2/".to_string(), "https://midgard.thorchain.liquify.com/v2/".to_string()]);
+	///
+	/// assert_eq!(config.get_base_urls().len(), 2);
+	/// ```
+	pub fn set_base_urls(&mut self, base_urls: Vec<String>) {
+		let mut base_urls = base_urls.into_iter();
+		if let Some(base_url) = base_urls.next() {
+			self.base_url = base_url;
+			self.fallback_base_urls = base_urls.collect();
+		}
+	}
+
+	pub const fn set_failover_policy(&mut self, failover_policy: FailoverPolicy) {
+		self.failover_policy = failover_policy;
+	}
+
 	pub fn set_rate_limit_ms(&mut self, rate_limit_ms: u64) {
 		self.rate_limit_ms = rate_limit_ms;
 	}
+
+	pub const fn set_rate_limit(&mut self, rate_limit: RateLimit) {
+		self.rate_limit_ms = rate_limit.get_interval_ms();
+		self.rate_limit_burst = rate_limit.get_burst();
+	}
+
+	pub fn set_group_rate_limit(&mut self, group: EndpointGroup, rate_limit: RateLimit) {
+		self.group_rate_limits.insert(group, rate_limit);
+	}
+
+	pub fn remove_group_rate_limit(&mut self, group: EndpointGroup) {
+		self.group_rate_limits.remove(&group);
+	}
+
+	pub const fn set_adaptive_rate_limit(&mut self, adaptive_rate_limit: bool) {
+		self.adaptive_rate_limit = adaptive_rate_limit;
+	}
+
+	pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+		self.retry_policy = retry_policy;
+	}
+
+	/// Sets a header sent with every request, replacing an earlier value of the same header.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use midgard_rs::Configuration;
+	///
+	/// let mut config = Configuration::default();
+	/// config.set_header("x-client-id", "my-dashboard");
+	/// config.set_user_agent("my-dashboard/1.0");
+	/// ```
+	pub fn set_header(&mut self, name: impl Into<String>, value: impl Into<String>) {
+		self.headers.insert(name.into(), value.into());
+	}
+
+	pub fn remove_header(&mut self, name: &str) {
+		self.headers.remove(name);
+	}
+
+	/// Sets the `User-Agent` header.
+	pub fn set_user_agent(&mut self, user_agent: impl Into<String>) {
+		self.set_header("User-Agent", user_agent);
+	}
+
+	pub fn set_auth(&mut self, auth: Option<Auth>) {
+		self.auth = auth;
+	}
+
+	/// Sets a provider whose headers are added to every request, after the default headers and `auth`.
+	/// The provider is not serialized.
+	pub fn set_auth_provider(&mut self, auth_provider: Option<Arc<dyn AuthProvider>>) {
+		self.auth_provider = auth_provider;
+	}
+
+	pub const fn set_timeouts(&mut self, timeouts: Timeouts) {
+		self.timeouts = timeouts;
+	}
+
+	pub fn set_cache_policy(&mut self, cache_policy: CachePolicy) {
+		self.cache_policy = cache_policy;
+	}
+
+	/// Enables the on-disk history store. The `_range` history methods then keep closed intervals in `history_store_dir`
+	/// and only request the windows that are not stored yet, so a backfill can be rerun offline.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use midgard_rs::Configuration;
+	///
+	/// let mut config = Configuration::default();
+	/// config.set_history_store_dir(Some(std::env::temp_dir().join("midgard-history")));
+	/// ```
+	pub fn set_history_store_dir(&mut self, history_store_dir: Option<PathBuf>) {
+		self.history_store_dir = history_store_dir;
+	}
+
+	/// Records the final response of each call to a cassette, or replays responses from one instead of calling Midgard. See `Cassette`.
+	pub fn set_cassette(&mut self, cassette: Option<Cassette>) {
+		self.cassette = cassette;
+	}
 }
 
 impl Default for Configuration {
 	fn default() -> Self {
-		Self { base_url: "https://midgard.ninerealms.com/v2/".to_string(), rate_limit_ms: 1000 }
+		Self {
+			base_url: "https://midgard.ninerealms.com/v2/".to_string(),
+			fallback_base_urls: vec![],
+			failover_policy: FailoverPolicy::default(),
+			rate_limit_ms: 1000,
+			rate_limit_burst: default_rate_limit_burst(),
+			group_rate_limits: HashMap::new(),
+			adaptive_rate_limit: default_adaptive_rate_limit(),
+			retry_policy: RetryPolicy::default(),
+			timeouts: Timeouts::default(),
+			cache_policy: CachePolicy::default(),
+			history_store_dir: None,
+			cassette: None,
+			headers: BTreeMap::new(),
+			auth: None,
+			auth_provider: None,
+		}
 	}
 }
+
+const fn default_rate_limit_burst() -> u32 {
+	1
+}
+
+const fn default_adaptive_rate_limit() -> bool {
+	true
+}
@@ -1,8 +1,8 @@
-use anyhow::Result;
-use chrono::Utc;
+use futures::stream::{self, Stream, TryStreamExt};
 
 use crate::Midgard;
-use crate::{api_get_action_list, ActionList, GetActionList};
+use crate::{api_get_action_list, Action, ActionList, GetActionList, MidgardError};
+use crate::trace::traced;
 
 impl Midgard {
 	/// List actions along with their related transactions. An action is generated by one or more inbound transactions with the intended action set in the transaction memo. The action may result in one or more outbound transactions. Results are paginated by sets of 50. Filters may be applied to query actions.
@@ -14,7 +14,9 @@ impl Midgard {
 	/// use midgard_rs::Midgard;
 	///
 	/// # tokio_test::block_on(async {
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	///
 	/// let params = GetActionList::new(vec!["BTC.BTC".to_string()], 10);
 	/// let actions = midgard.get_actions(params).await.unwrap();
@@ -23,13 +25,15 @@ impl Midgard {
 	/// # });
 	/// ```
 	///
-	/// To get paginated responses, you can pass the `next_page_token` from the previous response to the next request.
+	/// To get paginated responses, you can pass the `next_page_token` from the previous response to the next request, or use `actions_stream` which does this for you.
 	/// ```rust
 	/// use midgard_rs::GetActionList;
 	/// use midgard_rs::Midgard;
 	///
 	/// # tokio_test::block_on(async {
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	///
 	/// let mut params = GetActionList::new(vec!["BTC.BTC".to_string()], 10);
 	/// let actions = midgard.get_actions(params.clone()).await.unwrap();
@@ -48,11 +52,107 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-	pub async fn get_actions(&mut self, params: GetActionList) -> Result<ActionList> {
-		// Wait for rate limit timer
-		self.sleep_until_ok_to_call().await;
+	pub async fn get_actions(&self, params: GetActionList) -> Result<ActionList, MidgardError> {
+		traced!("get_actions"(params = ?params), api_get_action_list(self, params))
+	}
+
+	/// Streams every action matching `params`, fetching further pages as the stream is consumed.
+	///
+	/// Pages are followed with `nextPageToken`, walking back in time. If `params` walks forward (`from_timestamp`, `from_height` or `prev_page_token` is set), `prevPageToken` is followed instead.
+	/// The stream ends when Midgard returns an empty page or no further token. Every page request waits for the rate limiter.
+	/// Dropping the stream stops paging; a request in flight is cancelled and no further pages are fetched.
+	/// After an error the stream yields the error and ends.
+	/// The stream owns a clone of this instance, so it shares the rate limit budget and can be moved to another task.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use futures::{StreamExt, TryStreamExt};
+	/// use midgard_rs::GetActionList;
+	/// use midgard_rs::Midgard;
+	///
+	/// # tokio_test::block_on(async {
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
+	///
+	/// // the 120 most recent BTC.BTC actions, fetched in pages of 50
+	/// let params = GetActionList::new(vec!["BTC.BTC".to_string()], 50);
+	/// let actions: Vec<_> = midgard.actions_stream(params).take(120).try_collect().await.unwrap();
+	///
+	/// assert_eq!(actions.len(), 120);
+	/// # });
+	/// ```
+	pub fn actions_stream(&self, params: GetActionList) -> impl Stream<Item = Result<Action, MidgardError>> {
+		stream::try_unfold((self.clone(), Some(params)), |(midgard, params)| async move {
+			let Some(params) = params else {
+				return Ok::<_, MidgardError>(None);
+			};
+			let page = midgard.get_actions(params.clone()).await?;
+			if page.get_actions().is_empty() {
+				return Ok(None);
+			}
+			let next = params.next_page(page.get_meta());
+			Ok(Some((page.into_actions(), (midgard, next))))
+		})
+		.map_ok(|actions| stream::iter(actions.into_iter().map(Ok)))
+		.try_flatten()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use futures::StreamExt;
+
+	use super::*;
+	use crate::test_support::{MockMidgard, MockResponse};
+	use crate::RetryPolicy;
+
+	fn action_list(heights: &[u64], next_page_token: Option<u64>) -> String {
+		let actions = heights.iter().map(|height| format!(r#"{{"date":"1710527743635577563","height":"{height}","in":[],"metadata":{{}},"out":[],"pools":[],"status":"success","type":"swap"}}"#)).collect::<Vec<_>>().join(",");
+		let next_page_token = next_page_token.map_or_else(String::new, |token| token.to_string());
+		format!(r#"{{"actions":[{actions}],"count":"-1","meta":{{"nextPageToken":"{next_page_token}","prevPageToken":"9"}}}}"#)
+	}
+
+	fn midgard(server: &MockMidgard) -> Midgard {
+		let mut config = server.get_config();
+		config.set_retry_policy(RetryPolicy::none());
+		Midgard::with_config(config)
+	}
+
+	#[tokio::test]
+	async fn test_actions_stream_follows_page_tokens() {
+		let server = MockMidgard::start().await;
+		server.set_responses("actions", vec![MockResponse::new(200, &action_list(&[5, 4], Some(2))), MockResponse::new(200, &action_list(&[3, 2], Some(1))), MockResponse::new(200, &action_list(&[], None))]);
+		let midgard = midgard(&server);
+
+		let actions: Vec<Action> = midgard.actions_stream(GetActionList::new(vec!["BTC.BTC".to_string()], 2)).try_collect().await.unwrap();
+
+		assert_eq!(actions.iter().map(|action| *action.get_height()).collect::<Vec<_>>(), vec![5, 4, 3, 2]);
+		assert_eq!(server.get_requests().len(), 3);
+	}
+
+	#[tokio::test]
+	async fn test_actions_stream_stops_when_dropped() {
+		let server = MockMidgard::start().await;
+		server.set_response("actions", 200, &action_list(&[5, 4], Some(2)));
+		let midgard = midgard(&server);
+
+		let actions: Vec<_> = midgard.actions_stream(GetActionList::new(vec!["BTC.BTC".to_string()], 2)).take(2).collect().await;
+
+		assert_eq!(actions.len(), 2);
+		assert_eq!(server.get_requests().len(), 1);
+	}
+
+	#[tokio::test]
+	async fn test_actions_stream_ends_after_error() {
+		let server = MockMidgard::start().await;
+		server.set_responses("actions", vec![MockResponse::new(200, &action_list(&[5, 4], Some(2))), MockResponse::new(500, r#"{"error":"internal"}"#)]);
+		let midgard = midgard(&server);
+
+		let results: Vec<_> = midgard.actions_stream(GetActionList::new(vec!["BTC.BTC".to_string()], 2)).collect().await;
 
-		self.set_last_call(Utc::now());
-		api_get_action_list(self.get_config().get_base_url(), params).await
+		assert_eq!(results.len(), 3);
+		assert!(matches!(results[2], Err(MidgardError::Api { status: 500, .. })));
 	}
 }
@@ -1,7 +1,5 @@
-use anyhow::Result;
-use chrono::Utc;
-
-use crate::{api_get_balance, Balance, Midgard};
+use crate::{api_get_balance, Balance, Midgard, MidgardError};
+use crate::trace::traced;
 
 impl Midgard {
 	/// Returns all coin amounts of the given address at the specified timestamp or height, or at the latest process block if neither is provided.
@@ -12,7 +10,9 @@ impl Midgard {
 	/// ```rust
 	/// use midgard_rs::Midgard;
 	///# tokio_test::block_on(async {
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	/// let address = "thor102y0m3uptg0vvudeyh00r2fnz70wq7d8y7mu2g";
 	/// let balance = midgard.get_balance(address, None, None).await.unwrap();
 	/// assert!(*balance.get_height() > 0);
@@ -21,12 +21,8 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-	pub async fn get_balance(&mut self, address: &str, timestamp: Option<i64>, height: Option<u64>) -> Result<Balance> {
-		// Wait for rate limit timer
-		self.sleep_until_ok_to_call().await;
-
-		self.set_last_call(Utc::now());
-		api_get_balance(self.get_config().get_base_url(), address, timestamp, height).await
+	pub async fn get_balance(&self, address: &str, timestamp: Option<i64>, height: Option<u64>) -> Result<Balance, MidgardError> {
+		traced!("get_balance"(address = %address, timestamp = ?timestamp, height = ?height), api_get_balance(self, address, timestamp, height))
 	}
 }
 
@@ -34,11 +30,12 @@ impl Midgard {
 mod tests {
 	use serde_json::json;
 
-	use super::*;
+	use crate::test_support::MockMidgard;
 
 	#[tokio::test]
 	async fn test_get_balance() {
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		let address = "thor102y0m3uptg0vvudeyh00r2fnz70wq7d8y7mu2g";
 		let balance = midgard.get_balance(address, None, None).await.unwrap();
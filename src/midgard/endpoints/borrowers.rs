@@ -1,7 +1,5 @@
-use anyhow::Result;
-use chrono::Utc;
-
-use crate::{api_get_borrowers_details, api_get_borrowers_list, BorrowersDetails, BorrowersList, Midgard};
+use crate::{api_get_borrowers_details, api_get_borrowers_list, BorrowersDetails, BorrowersList, Midgard, MidgardError};
+use crate::trace::traced;
 
 impl Midgard {
 	/// Returns an array of statistics for all the open loans associated with a given borrower address.
@@ -14,7 +12,9 @@ impl Midgard {
 	///
 	/// # tokio_test::block_on(async {
 	/// // Create a new instance of Midgard
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	///
 	/// // Get two random addresses from the list of borrowers
 	/// let borrowers = midgard.get_borrowers_list(None).await.unwrap();
@@ -31,12 +31,8 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-	pub async fn get_borrowers_details(&mut self, address: &str) -> Result<BorrowersDetails> {
-		// Wait for rate limit timer
-		self.sleep_until_ok_to_call().await;
-
-		self.set_last_call(Utc::now());
-		api_get_borrowers_details(self.get_config().get_base_url(), address).await
+	pub async fn get_borrowers_details(&self, address: &str) -> Result<BorrowersDetails, MidgardError> {
+		traced!("get_borrowers_details"(address = %address), api_get_borrowers_details(self, address))
 	}
 
 	/// Returns an array containing the addresses for all borrowers. Addresses are only shown once.
@@ -48,7 +44,9 @@ impl Midgard {
 	///
 	/// # tokio_test::block_on(async {
 	/// // Create a new instance of Midgard
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	///
 	/// // Set the asset
 	/// let asset = Some("BTC.BTC".to_string());
@@ -62,12 +60,8 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-	pub async fn get_borrowers_list(&mut self, asset: Option<String>) -> Result<BorrowersList> {
-		// Wait for rate limit timer
-		self.sleep_until_ok_to_call().await;
-
-		self.set_last_call(Utc::now());
-		api_get_borrowers_list(self.get_config().get_base_url(), asset).await
+	pub async fn get_borrowers_list(&self, asset: Option<String>) -> Result<BorrowersList, MidgardError> {
+		traced!("get_borrowers_list"(asset = ?asset), api_get_borrowers_list(self, asset))
 	}
 }
 
@@ -76,12 +70,13 @@ mod tests {
 	use rand::prelude::*;
 	use serde_json::json;
 
-	use super::*;
+	use crate::test_support::MockMidgard;
 
 	#[tokio::test]
 	async fn test_get_borrowers_details() {
 		// Create a new instance of Midgard
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		// Get two random addresses from the list of borrowers
 		let borrowers = midgard.get_borrowers_list(None).await.unwrap();
@@ -100,7 +95,8 @@ mod tests {
 	#[tokio::test]
 	async fn test_get_borrowers_list() {
 		// Create a new instance of Midgard
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		// Set the asset
 		let asset = Some("BTC.BTC".to_string());
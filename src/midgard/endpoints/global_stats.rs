@@ -1,7 +1,5 @@
-use anyhow::Result;
-use chrono::Utc;
-
-use crate::{api_get_global_stats, GlobalStats, Midgard};
+use crate::{api_get_global_stats, GlobalStats, Midgard, MidgardError};
+use crate::trace::traced;
 
 impl Midgard {
 	/// Returns an object containing global stats for all pools and all transactions.
@@ -13,7 +11,9 @@ impl Midgard {
 	///
 	/// # tokio_test::block_on(async {
 	/// // Create a new instance of Midgard
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	///
 	/// // Get the global stats
 	/// let global_stats = midgard.get_global_stats().await.unwrap();
@@ -24,12 +24,8 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-	pub async fn get_global_stats(&mut self) -> Result<GlobalStats> {
-		// Wait for rate limit timer
-		self.sleep_until_ok_to_call().await;
-
-		self.set_last_call(Utc::now());
-		api_get_global_stats(self.get_config().get_base_url()).await
+	pub async fn get_global_stats(&self) -> Result<GlobalStats, MidgardError> {
+		traced!("get_global_stats", api_get_global_stats(self))
 	}
 }
 
@@ -37,12 +33,13 @@ impl Midgard {
 mod tests {
 	use serde_json::json;
 
-	use super::*;
+	use crate::test_support::MockMidgard;
 
 	#[tokio::test]
 	async fn test_get_global_stats() {
 		// Create a new instance of Midgard
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		// Get the global stats
 		let global_stats = midgard.get_global_stats().await.unwrap();
@@ -1,8 +1,6 @@
-use anyhow::Result;
-use chrono::Utc;
-
 use crate::Midgard;
-use crate::{api_get_health_info, HealthInfo};
+use crate::{api_get_health_info, HealthInfo, MidgardError};
+use crate::trace::traced;
 
 impl Midgard {
 	/// Returns an object containing the health response of the API. Meaning of heights:
@@ -20,7 +18,9 @@ impl Midgard {
 	///
 	/// # tokio_test::block_on(async {
 	/// // Create a new instance of Midgard
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	///
 	/// // Get the health info
 	/// let health_info = midgard.get_health_info().await.unwrap();
@@ -36,12 +36,8 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-	pub async fn get_health_info(&mut self) -> Result<HealthInfo> {
-		// Wait for rate limit timer
-		self.sleep_until_ok_to_call().await;
-
-		self.set_last_call(Utc::now());
-		api_get_health_info(self.get_config().get_base_url()).await
+	pub async fn get_health_info(&self) -> Result<HealthInfo, MidgardError> {
+		traced!("get_health_info", api_get_health_info(self))
 	}
 }
 
@@ -49,12 +45,13 @@ impl Midgard {
 mod tests {
 	use serde_json::json;
 
-	use crate::Midgard;
+	use crate::test_support::MockMidgard;
 
 	#[tokio::test]
 	async fn test_get_health_info() {
 		// Create a new instance of Midgard
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		// Get the health info
 		let health_info = midgard.get_health_info().await.unwrap();
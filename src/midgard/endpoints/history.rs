@@ -1,7 +1,14 @@
-use anyhow::Result;
-use chrono::Utc;
+use std::future::Future;
+use std::ops::Range;
 
-use crate::{api_get_depth_and_price_history, api_get_earnings_history, api_get_liquidity_change_history, api_get_savers_units_and_depth_history, api_get_swaps_history, api_get_total_value_locked_history, DepthHistory, EarningsHistory, Interval, LiquidityChangeHistory, Midgard, SaversHistory, SwapHistory, TVLHistory};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::midgard::history_store::HistoryStore;
+use crate::midgard::paginator::{HistoryPaginator, PagedHistory};
+use crate::{api_get_depth_and_price_history, api_get_earnings_history, api_get_liquidity_change_history, api_get_savers_units_and_depth_history, api_get_swaps_history, api_get_total_value_locked_history, DepthHistory, EarningsHistory, HistoryQuery, Interval, LiquidityChangeHistory, Midgard, MidgardError, SaversHistory, SwapHistory, TVLHistory};
+use crate::trace::{event, traced};
 
 impl Midgard {
 	/// Returns the asset and rune depths and price. The values report the state at the end of each interval.
@@ -9,8 +16,8 @@ impl Midgard {
 	/// * With Interval parameter it returns a series of time buckets. From and To dates will be rounded to the Interval boundaries.
 	/// * Without Interval parameter a single From..To search is performed with exact timestamps.
 	/// * Interval: possible values: 5min, hour, day, week, month, quarter, year.
-	/// * count: [1..400]. Defines number of intervals. Don't provide if Interval is missing.
-	/// * from/to: optional int, unix second.
+	/// * count: [1..400]. Defines number of intervals. Rejected by `HistoryQueryBuilder::build` if Interval is missing.
+	/// * from/to: optional `DateTime<Utc>`, set individually or as a `Range<DateTime<Utc>>`.
 	///
 	/// Possible usages with interval.
 	/// * last 10 days: ?interval=day&count=10
@@ -19,6 +26,7 @@ impl Midgard {
 	/// * Days between from and to. From defaults to start of chain, to defaults to now. Only the first 400 intervals are returned: interval=day&from=1606780800&to=1608825600
 	///
 	/// Pagination is possible with from&count and then using the returned meta.endTime as the From parameter of the next query.
+	/// `get_depth_and_price_history_range` does this for ranges of any length.
 	///
 	/// Possible configurations without interval:
 	/// * exact search for one time frame: ?from=1606780899&to=1608825600
@@ -29,14 +37,16 @@ impl Midgard {
 	///
 	/// ```rust
 	/// use midgard_rs::Midgard;
-	/// use midgard_rs::Interval;
+	/// use midgard_rs::{HistoryQuery, Interval};
 	///
 	/// # tokio_test::block_on(async {
 	/// // Create a new instance of Midgard
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	///
 	/// // Get depth & price history
-	/// let depth_history = midgard.get_depth_and_price_history("BTC.BTC", Some(Interval::Day), Some(10), None, None).await.unwrap();
+	/// let depth_history = midgard.get_depth_and_price_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 	///
 	/// assert!(!depth_history.get_intervals().is_empty());
 	/// # });
@@ -46,22 +56,23 @@ impl Midgard {
 	///
 	/// ```
 	/// use midgard_rs::Midgard;
-	/// use midgard_rs::Interval;
+	/// use midgard_rs::{HistoryQuery, Interval};
 	///
-	/// # std::thread::sleep(std::time::Duration::from_secs(10));
 	/// # tokio_test::block_on(async {
 	/// // Create a new instance of Midgard
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	///
 	/// // Get depth & price history
-	/// let depth_history = midgard.get_depth_and_price_history("BTC.BTC", Some(Interval::Day), Some(10), None, None).await.unwrap();
+	/// let depth_history = midgard.get_depth_and_price_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 	///
 	/// assert!(!depth_history.get_intervals().is_empty());
 	///
 	/// // Get the end time
-	/// let end_time = depth_history.get_meta().get_end_time().timestamp() as u64;
+	/// let end_time = *depth_history.get_meta().get_end_time();
 	///
-	/// let depth_history = midgard.get_depth_and_price_history("BTC.BTC", Some(Interval::Day), Some(10), None, Some(end_time)).await.unwrap();
+	/// let depth_history = midgard.get_depth_and_price_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).from(end_time).build().unwrap()).await.unwrap();
 	///
 	/// assert!(!depth_history.get_intervals().is_empty());
 	/// # });
@@ -69,12 +80,8 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-	pub async fn get_depth_and_price_history(&mut self, pool: &str, interval: Option<Interval>, count: Option<usize>, to: Option<u64>, from: Option<u64>) -> Result<DepthHistory> {
-		// Wait for rate limit timer
-		self.sleep_until_ok_to_call().await;
-
-		self.set_last_call(Utc::now());
-		api_get_depth_and_price_history(self.get_config().get_base_url(), pool, interval, count, to, from).await
+	pub async fn get_depth_and_price_history(&self, pool: &str, query: &HistoryQuery) -> Result<DepthHistory, MidgardError> {
+		traced!("get_depth_and_price_history"(pool = %pool, interval = ?query.get_interval(), count = ?query.get_count()), api_get_depth_and_price_history(self, pool, query))
 	}
 
 	/// Returns earnings data for the specified interval.
@@ -83,8 +90,8 @@ impl Midgard {
 	/// * With Interval parameter it returns a series of time buckets. From and To dates will be rounded to the Interval boundaries.
 	/// * Without Interval parameter a single From..To search is performed with exact timestamps.
 	/// * Interval: possible values: 5min, hour, day, week, month, quarter, year.
-	/// * count: [1..400]. Defines number of intervals. Don't provide if Interval is missing.
-	/// * from/to: optional int, unix second.
+	/// * count: [1..400]. Defines number of intervals. Rejected by `HistoryQueryBuilder::build` if Interval is missing.
+	/// * from/to: optional `DateTime<Utc>`, set individually or as a `Range<DateTime<Utc>>`.
 	///
 	/// Possible usages with interval.
 	/// * last 10 days: ?interval=day&count=10
@@ -93,6 +100,7 @@ impl Midgard {
 	/// * Days between from and to. From defaults to start of chain, to defaults to now. Only the first 400 intervals are returned: interval=day&from=1606780800&to=1608825600
 	///
 	/// Pagination is possible with from&count and then using the returned meta.endTime as the From parameter of the next query.
+	/// `get_earnings_history_range` does this for ranges of any length.
 	///
 	/// Possible configurations without interval:
 	/// * exact search for one time frame: ?from=1606780899&to=1608825600
@@ -103,14 +111,16 @@ impl Midgard {
 	///
 	/// ```rust
 	/// use midgard_rs::Midgard;
-	/// use midgard_rs::Interval;
+	/// use midgard_rs::{HistoryQuery, Interval};
 	///
 	/// # tokio_test::block_on(async {
 	/// // Create a new instance of Midgard
-	///let mut midgard = Midgard::new();
+	///let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	///
 	///// Get depth & price history
-	///let depth_history = midgard.get_depth_and_price_history("BTC.BTC", Some(Interval::Day), Some(10), None, None).await.unwrap();
+	///let depth_history = midgard.get_depth_and_price_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 	///
 	///assert!(!depth_history.get_intervals().is_empty());
 	/// # });
@@ -119,22 +129,23 @@ impl Midgard {
 	/// To get paginated responses, you can pass the `end_time` from the previous response to the next request.
 	/// ```rust
 	/// use midgard_rs::Midgard;
-	/// use midgard_rs::Interval;
+	/// use midgard_rs::{HistoryQuery, Interval};
 	///
-	/// # std::thread::sleep(std::time::Duration::from_secs(10));
 	/// # tokio_test::block_on(async {
 	/// // Create a new instance of Midgard
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	///
 	/// // Get depth & price history
-	/// let depth_history = midgard.get_depth_and_price_history("BTC.BTC", Some(Interval::Day), Some(10), None, None).await.unwrap();
+	/// let depth_history = midgard.get_depth_and_price_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 	///
 	/// assert!(!depth_history.get_intervals().is_empty());
 	///
 	/// // Get the end time
-	/// let end_time = depth_history.get_meta().get_end_time().timestamp() as u64;
+	/// let end_time = *depth_history.get_meta().get_end_time();
 	///
-	/// let depth_history = midgard.get_depth_and_price_history("BTC.BTC", Some(Interval::Day), Some(10), None, Some(end_time)).await.unwrap();
+	/// let depth_history = midgard.get_depth_and_price_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).from(end_time).build().unwrap()).await.unwrap();
 	///
 	/// assert!(!depth_history.get_intervals().is_empty());
 	/// # });
@@ -142,12 +153,8 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-	pub async fn get_earnings_history(&mut self, interval: Option<Interval>, count: Option<usize>, to: Option<u64>, from: Option<u64>) -> Result<EarningsHistory> {
-		// Wait for rate limit timer
-		self.sleep_until_ok_to_call().await;
-
-		self.set_last_call(Utc::now());
-		api_get_earnings_history(self.get_config().get_base_url(), interval, count, to, from).await
+	pub async fn get_earnings_history(&self, query: &HistoryQuery) -> Result<EarningsHistory, MidgardError> {
+		traced!("get_earnings_history"(interval = ?query.get_interval(), count = ?query.get_count()), api_get_earnings_history(self, query))
 	}
 
 	/// Returns withdrawals and deposits for given time interval. If pool is not specified returns for all pools
@@ -156,8 +163,8 @@ impl Midgard {
 	/// * With Interval parameter it returns a series of time buckets. From and To dates will be rounded to the Interval boundaries.
 	/// * Without Interval parameter a single From..To search is performed with exact timestamps.
 	/// * Interval: possible values: 5min, hour, day, week, month, quarter, year.
-	/// * count: [1..400]. Defines number of intervals. Don't provide if Interval is missing.
-	/// * from/to: optional int, unix second.
+	/// * count: [1..400]. Defines number of intervals. Rejected by `HistoryQueryBuilder::build` if Interval is missing.
+	/// * from/to: optional `DateTime<Utc>`, set individually or as a `Range<DateTime<Utc>>`.
 	///
 	/// Possible usages with interval.
 	/// * last 10 days: ?interval=day&count=10
@@ -166,6 +173,7 @@ impl Midgard {
 	/// * Days between from and to. From defaults to start of chain, to defaults to now. Only the first 400 intervals are returned: interval=day&from=1606780800&to=1608825600
 	///
 	/// Pagination is possible with from&count and then using the returned meta.endTime as the From parameter of the next query.
+	/// `get_liquidity_change_history_range` does this for ranges of any length.
 	///
 	/// Possible configurations without interval:
 	/// * exact search for one time frame: ?from=1606780899&to=1608825600
@@ -175,14 +183,16 @@ impl Midgard {
 	/// # Example
 	/// ```rust
 	/// use midgard_rs::Midgard;
-	/// use midgard_rs::Interval;
+	/// use midgard_rs::{HistoryQuery, Interval};
 	///
 	/// # tokio_test::block_on(async {
 	/// // Create a new instance of Midgard
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	///
 	/// // Get liquidity change history
-	/// let liquidity_change_history = midgard.get_liquidity_change_history("BTC.BTC", Some(Interval::Day), Some(10), None, None).await.unwrap();
+	/// let liquidity_change_history = midgard.get_liquidity_change_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 	///
 	/// assert!(!liquidity_change_history.get_intervals().is_empty());
 	/// # });
@@ -191,22 +201,23 @@ impl Midgard {
 	/// To get paginated responses, you can pass the `end_time` from the previous response to the next request.
 	/// ```rust
 	/// use midgard_rs::Midgard;
-	/// use midgard_rs::Interval;
+	/// use midgard_rs::{HistoryQuery, Interval};
 	///
-	/// # std::thread::sleep(std::time::Duration::from_secs(10));
 	/// # tokio_test::block_on(async {
 	/// // Create a new instance of Midgard
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	///
 	/// // Get liquidity change history
-	/// let liquidity_change_history = midgard.get_liquidity_change_history("BTC.BTC", Some(Interval::Day), Some(10), None, None).await.unwrap();
+	/// let liquidity_change_history = midgard.get_liquidity_change_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 	///
 	/// assert!(!liquidity_change_history.get_intervals().is_empty());
 	///
 	/// // Get the end time
-	/// let end_time = liquidity_change_history.get_meta().get_end_time().timestamp() as u64;
+	/// let end_time = *liquidity_change_history.get_meta().get_end_time();
 	///
-	/// let liquidity_change_history = midgard.get_liquidity_change_history("BTC.BTC", Some(Interval::Day), Some(10), None, Some(end_time)).await.unwrap();
+	/// let liquidity_change_history = midgard.get_liquidity_change_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).from(end_time).build().unwrap()).await.unwrap();
 	///
 	/// assert!(!liquidity_change_history.get_intervals().is_empty());
 	/// # });
@@ -214,12 +225,8 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-	pub async fn get_liquidity_change_history(&mut self, pool: &str, interval: Option<Interval>, count: Option<usize>, to: Option<u64>, from: Option<u64>) -> Result<LiquidityChangeHistory> {
-		// Wait for rate limit timer
-		self.sleep_until_ok_to_call().await;
-
-		self.set_last_call(Utc::now());
-		api_get_liquidity_change_history(self.get_config().get_base_url(), pool, interval, count, to, from).await
+	pub async fn get_liquidity_change_history(&self, pool: &str, query: &HistoryQuery) -> Result<LiquidityChangeHistory, MidgardError> {
+		traced!("get_liquidity_change_history"(pool = %pool, interval = ?query.get_interval(), count = ?query.get_count()), api_get_liquidity_change_history(self, pool, query))
 	}
 
 	/// Returns savers depths and units. The values report the state at the end of each interval.
@@ -228,8 +235,8 @@ impl Midgard {
 	/// * With Interval parameter it returns a series of time buckets. From and To dates will be rounded to the Interval boundaries.
 	/// * Without Interval parameter a single From..To search is performed with exact timestamps.
 	/// * Interval: possible values: 5min, hour, day, week, month, quarter, year.
-	/// * count: [1..400]. Defines number of intervals. Don't provide if Interval is missing.
-	/// * from/to: optional int, unix second.
+	/// * count: [1..400]. Defines number of intervals. Rejected by `HistoryQueryBuilder::build` if Interval is missing.
+	/// * from/to: optional `DateTime<Utc>`, set individually or as a `Range<DateTime<Utc>>`.
 	///
 	/// Possible usages with interval.
 	/// * last 10 days: ?interval=day&count=10
@@ -238,6 +245,7 @@ impl Midgard {
 	/// * Days between from and to. From defaults to start of chain, to defaults to now. Only the first 400 intervals are returned: interval=day&from=1606780800&to=1608825600
 	///
 	/// Pagination is possible with from&count and then using the returned meta.endTime as the From parameter of the next query.
+	/// `get_savers_units_and_depth_history_range` does this for ranges of any length.
 	///
 	/// Possible configurations without interval:
 	/// * exact search for one time frame: ?from=1606780899&to=1608825600
@@ -247,14 +255,16 @@ impl Midgard {
 	/// # Example
 	/// ```rust
 	/// use midgard_rs::Midgard;
-	/// use midgard_rs::Interval;
+	/// use midgard_rs::{HistoryQuery, Interval};
 	///
 	/// # tokio_test::block_on(async {
 	/// // Create a new instance of Midgard
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	///
 	/// // Get savers units and depth history
-	/// let savers_history = midgard.get_savers_units_and_depth_history("BTC.BTC", Some(Interval::Day), Some(10), None, None).await.unwrap();
+	/// let savers_history = midgard.get_savers_units_and_depth_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 	///
 	/// assert!(!savers_history.get_intervals().is_empty());
 	/// # });
@@ -263,32 +273,29 @@ impl Midgard {
 	/// To get paginated responses, you can pass the `end_time` from the previous response to the next request.
 	/// ```rust
 	/// use midgard_rs::Midgard;
-	/// use midgard_rs::Interval;
+	/// use midgard_rs::{HistoryQuery, Interval};
 	///
-	/// # std::thread::sleep(std::time::Duration::from_secs(10));
 	/// # tokio_test::block_on(async {
 	/// // Create a new instance of Midgard
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	///
 	/// // Get savers units and depth history
-	/// let savers_history = midgard.get_savers_units_and_depth_history("BTC.BTC", Some(Interval::Day), Some(10), None, None).await.unwrap();
+	/// let savers_history = midgard.get_savers_units_and_depth_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 	/// assert!(!savers_history.get_intervals().is_empty());
 	///
 	/// // Get the end time
-	/// let end_time = savers_history.get_meta().get_end_time().timestamp() as u64;
-	/// let savers_history = midgard.get_savers_units_and_depth_history("BTC.BTC", Some(Interval::Day), Some(10), None, Some(end_time)).await.unwrap();
+	/// let end_time = *savers_history.get_meta().get_end_time();
+	/// let savers_history = midgard.get_savers_units_and_depth_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).from(end_time).build().unwrap()).await.unwrap();
 	/// assert!(!savers_history.get_intervals().is_empty());
 	/// # });
 	/// ```
         /// 
         /// # Errors
         /// todo
-	pub async fn get_savers_units_and_depth_history(&mut self, pool: &str, interval: Option<Interval>, count: Option<usize>, to: Option<u64>, from: Option<u64>) -> Result<SaversHistory> {
-		// Wait for rate limit timer
-		self.sleep_until_ok_to_call().await;
-
-		self.set_last_call(Utc::now());
-		api_get_savers_units_and_depth_history(self.get_config().get_base_url(), pool, interval, count, to, from).await
+	pub async fn get_savers_units_and_depth_history(&self, pool: &str, query: &HistoryQuery) -> Result<SaversHistory, MidgardError> {
+		traced!("get_savers_units_and_depth_history"(pool = %pool, interval = ?query.get_interval(), count = ?query.get_count()), api_get_savers_units_and_depth_history(self, pool, query))
 	}
 
 	/// Returns swap count, volume, fees, slip in specified interval. If pool is not specified returns for all pools
@@ -297,8 +304,8 @@ impl Midgard {
 	/// * With Interval parameter it returns a series of time buckets. From and To dates will be rounded to the Interval boundaries.
 	/// * Without Interval parameter a single From..To search is performed with exact timestamps.
 	/// * Interval: possible values: 5min, hour, day, week, month, quarter, year.
-	/// * count: [1..400]. Defines number of intervals. Don't provide if Interval is missing.
-	/// * from/to: optional int, unix second.
+	/// * count: [1..400]. Defines number of intervals. Rejected by `HistoryQueryBuilder::build` if Interval is missing.
+	/// * from/to: optional `DateTime<Utc>`, set individually or as a `Range<DateTime<Utc>>`.
 	///
 	/// Possible usages with interval.
 	/// * last 10 days: ?interval=day&count=10
@@ -307,6 +314,7 @@ impl Midgard {
 	/// * Days between from and to. From defaults to start of chain, to defaults to now. Only the first 400 intervals are returned: interval=day&from=1606780800&to=1608825600
 	///
 	/// Pagination is possible with from&count and then using the returned meta.endTime as the From parameter of the next query.
+	/// `get_swaps_history_range` does this for ranges of any length.
 	///
 	/// Possible configurations without interval:
 	/// * exact search for one time frame: ?from=1606780899&to=1608825600
@@ -316,13 +324,15 @@ impl Midgard {
 	/// # Example
 	/// ```rust
 	/// use midgard_rs::Midgard;
-	/// use midgard_rs::Interval;
+	/// use midgard_rs::{HistoryQuery, Interval};
 	///
 	/// # tokio_test::block_on(async {
 	/// // Create a new instance of Midgard
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	/// // Get swaps history
-	/// let swaps_history = midgard.get_swaps_history(None, Some(Interval::Day), Some(10), None, None).await.unwrap();
+	/// let swaps_history = midgard.get_swaps_history(None, &HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 	/// assert!(!swaps_history.get_intervals().is_empty());
 	/// # });
 	/// ```
@@ -330,30 +340,28 @@ impl Midgard {
 	/// To get paginated responses, you can pass the `end_time` from the previous response to the next request.
 	/// ```rust
 	/// use midgard_rs::Midgard;
-	/// use midgard_rs::Interval;
+	/// use midgard_rs::{HistoryQuery, Interval};
 	///
-	/// # std::thread::sleep(std::time::Duration::from_secs(10));
 	/// # tokio_test::block_on(async {
 	/// // Create a new instance of Midgard
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	/// // Get swaps history
-	/// let swaps_history = midgard.get_swaps_history(None, Some(Interval::Day), Some(10), None, None).await.unwrap();
+	/// let swaps_history = midgard.get_swaps_history(None, &HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 	/// assert!(!swaps_history.get_intervals().is_empty());
 	///
 	/// // Get the end time
-	/// let end_time = swaps_history.get_meta().get_end_time().timestamp() as u64;
-	/// let swaps_history = midgard.get_swaps_history(None, Some(Interval::Day), Some(10), None, Some(end_time)).await.unwrap();
+	/// let end_time = *swaps_history.get_meta().get_end_time();
+	/// let swaps_history = midgard.get_swaps_history(None, &HistoryQuery::builder().interval(Interval::Day).count(10).from(end_time).build().unwrap()).await.unwrap();
 	/// assert!(!swaps_history.get_intervals().is_empty());
 	/// # });
 	/// ```
         /// 
         /// # Errors
         /// todo
-	pub async fn get_swaps_history(&mut self, pool: Option<&str>, interval: Option<Interval>, count: Option<usize>, to: Option<u64>, from: Option<u64>) -> Result<SwapHistory> {
-		// Wait for rate limit timer
-		self.sleep_until_ok_to_call().await;
-
-		api_get_swaps_history(self.get_config().get_base_url(), pool, interval, count, to, from).await
+	pub async fn get_swaps_history(&self, pool: Option<&str>, query: &HistoryQuery) -> Result<SwapHistory, MidgardError> {
+		traced!("get_swaps_history"(pool = ?pool, interval = ?query.get_interval(), count = ?query.get_count()), api_get_swaps_history(self, pool, query))
 	}
 
 	/// Returns total pool depths, total bonds, and total value locked in specified interval.
@@ -364,8 +372,8 @@ impl Midgard {
 	/// * With Interval parameter it returns a series of time buckets. From and To dates will be rounded to the Interval boundaries.
 	/// * Without Interval parameter a single From..To search is performed with exact timestamps.
 	/// * Interval: possible values: 5min, hour, day, week, month, quarter, year.
-	/// * count: [1..400]. Defines number of intervals. Don't provide if Interval is missing.
-	/// * from/to: optional int, unix second.
+	/// * count: [1..400]. Defines number of intervals. Rejected by `HistoryQueryBuilder::build` if Interval is missing.
+	/// * from/to: optional `DateTime<Utc>`, set individually or as a `Range<DateTime<Utc>>`.
 	///
 	/// Possible usages with interval.
 	/// * last 10 days: ?interval=day&count=10
@@ -374,6 +382,7 @@ impl Midgard {
 	/// * Days between from and to. From defaults to start of chain, to defaults to now. Only the first 400 intervals are returned: interval=day&from=1606780800&to=1608825600
 	///
 	/// Pagination is possible with from&count and then using the returned meta.endTime as the From parameter of the next query.
+	/// `get_total_value_locked_history_range` does this for ranges of any length.
 	///
 	/// Possible configurations without interval:
 	/// * exact search for one time frame: ?from=1606780899&to=1608825600
@@ -383,13 +392,15 @@ impl Midgard {
 	/// # Example
 	/// ```rust
 	/// use midgard_rs::Midgard;
-	/// use midgard_rs::Interval;
+	/// use midgard_rs::{HistoryQuery, Interval};
 	///
 	/// # tokio_test::block_on(async {
 	/// // Create a new instance of Midgard
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	/// // Get total value locked history
-	/// let tvl_history = midgard.get_total_value_locked_history(Some(Interval::Day), Some(10), None, None).await.unwrap();
+	/// let tvl_history = midgard.get_total_value_locked_history(&HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 	/// assert!(!tvl_history.get_intervals().is_empty());
 	/// # });
 	/// ```
@@ -397,47 +408,316 @@ impl Midgard {
 	/// To get paginated responses, you can pass the `end_time` from the previous response to the next request.
 	/// ```rust
 	/// use midgard_rs::Midgard;
-	/// use midgard_rs::Interval;
+	/// use midgard_rs::{HistoryQuery, Interval};
 	///
-	/// # std::thread::sleep(std::time::Duration::from_secs(10));
 	/// # tokio_test::block_on(async {
 	/// // Create a new instance of Midgard
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	/// // Get total value locked history
-	/// let tvl_history = midgard.get_total_value_locked_history(Some(Interval::Day), Some(10), None, None).await.unwrap();
+	/// let tvl_history = midgard.get_total_value_locked_history(&HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 	/// assert!(!tvl_history.get_intervals().is_empty());
 	///
 	/// // Get the end time
-	/// let end_time = tvl_history.get_meta().get_end_time().timestamp() as u64;
-	/// let tvl_history = midgard.get_total_value_locked_history(Some(Interval::Day), Some(10), None, Some(end_time)).await.unwrap();
+	/// let end_time = *tvl_history.get_meta().get_end_time();
+	/// let tvl_history = midgard.get_total_value_locked_history(&HistoryQuery::builder().interval(Interval::Day).count(10).from(end_time).build().unwrap()).await.unwrap();
 	/// assert!(!tvl_history.get_intervals().is_empty());
 	/// # });
 	/// ```
         /// 
         /// # Errors
         /// todo
-	pub async fn get_total_value_locked_history(&mut self, interval: Option<Interval>, count: Option<usize>, to: Option<u64>, from: Option<u64>) -> Result<TVLHistory> {
-		// Wait for rate limit timer
-		self.sleep_until_ok_to_call().await;
+	pub async fn get_total_value_locked_history(&self, query: &HistoryQuery) -> Result<TVLHistory, MidgardError> {
+		traced!("get_total_value_locked_history"(interval = ?query.get_interval(), count = ?query.get_count()), api_get_total_value_locked_history(self, query))
+	}
 
-		self.set_last_call(Utc::now());
-		api_get_total_value_locked_history(self.get_config().get_base_url(), interval, count, to, from).await
+	/// Returns the depth and price history for every `interval` in `range`, however many there are, fetched page by page as described in [History Ranges](crate#history-ranges).
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use chrono::{Duration, Utc};
+	/// use midgard_rs::{Interval, Midgard};
+	///
+	/// # tokio_test::block_on(async {
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
+	///
+	/// // two years of daily intervals, more than a single request can return
+	/// let now = Utc::now();
+	/// let history = midgard.get_depth_and_price_history_range("BTC.BTC", Interval::Day, now - Duration::days(730)..now).await.unwrap();
+	///
+	/// assert!(history.get_intervals().get_intervals().len() > 400);
+	/// # });
+	/// ```
+	///
+	/// # Errors
+	/// Any error returned while fetching one of the pages or reading and writing the history store.
+	pub async fn get_depth_and_price_history_range(&self, pool: &str, interval: Interval, range: Range<DateTime<Utc>>) -> Result<DepthHistory, MidgardError> {
+		traced!("get_depth_and_price_history_range"(pool = %pool, interval = %interval, from = %range.start, to = %range.end), self.history_range("depths", Some(pool), interval, range, |query| async move { self.get_depth_and_price_history(pool, &query).await }))
+	}
+
+	/// Returns the earnings history for every `interval` in `range`, however many there are, fetched page by page as described in [History Ranges](crate#history-ranges).
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use chrono::{Duration, Utc};
+	/// use midgard_rs::{Interval, Midgard};
+	///
+	/// # tokio_test::block_on(async {
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
+	///
+	/// // two years of daily intervals, more than a single request can return
+	/// let now = Utc::now();
+	/// let history = midgard.get_earnings_history_range(Interval::Day, now - Duration::days(730)..now).await.unwrap();
+	///
+	/// assert!(history.get_intervals().get_intervals().len() > 400);
+	/// # });
+	/// ```
+	///
+	/// # Errors
+	/// Any error returned while fetching one of the pages or reading and writing the history store.
+	pub async fn get_earnings_history_range(&self, interval: Interval, range: Range<DateTime<Utc>>) -> Result<EarningsHistory, MidgardError> {
+		traced!("get_earnings_history_range"(interval = %interval, from = %range.start, to = %range.end), self.history_range("earnings", None, interval, range, |query| async move { self.get_earnings_history(&query).await }))
+	}
+
+	/// Returns the liquidity change history for every `interval` in `range`, however many there are, fetched page by page as described in [History Ranges](crate#history-ranges).
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use chrono::{Duration, Utc};
+	/// use midgard_rs::{Interval, Midgard};
+	///
+	/// # tokio_test::block_on(async {
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
+	///
+	/// // two years of daily intervals, more than a single request can return
+	/// let now = Utc::now();
+	/// let history = midgard.get_liquidity_change_history_range("BTC.BTC", Interval::Day, now - Duration::days(730)..now).await.unwrap();
+	///
+	/// assert!(history.get_intervals().get_liquidity_change_intervals().len() > 400);
+	/// # });
+	/// ```
+	///
+	/// # Errors
+	/// Any error returned while fetching one of the pages or reading and writing the history store.
+	pub async fn get_liquidity_change_history_range(&self, pool: &str, interval: Interval, range: Range<DateTime<Utc>>) -> Result<LiquidityChangeHistory, MidgardError> {
+		traced!("get_liquidity_change_history_range"(pool = %pool, interval = %interval, from = %range.start, to = %range.end), self.history_range("liquidity_changes", Some(pool), interval, range, |query| async move { self.get_liquidity_change_history(pool, &query).await }))
+	}
+
+	/// Returns the savers units and depth history for every `interval` in `range`, however many there are, fetched page by page as described in [History Ranges](crate#history-ranges).
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use chrono::{Duration, Utc};
+	/// use midgard_rs::{Interval, Midgard};
+	///
+	/// # tokio_test::block_on(async {
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
+	///
+	/// // two years of daily intervals, more than a single request can return
+	/// let now = Utc::now();
+	/// let history = midgard.get_savers_units_and_depth_history_range("BTC.BTC", Interval::Day, now - Duration::days(730)..now).await.unwrap();
+	///
+	/// assert!(history.get_intervals().get_saver_history_intervals().len() > 400);
+	/// # });
+	/// ```
+	///
+	/// # Errors
+	/// Any error returned while fetching one of the pages or reading and writing the history store.
+	pub async fn get_savers_units_and_depth_history_range(&self, pool: &str, interval: Interval, range: Range<DateTime<Utc>>) -> Result<SaversHistory, MidgardError> {
+		traced!("get_savers_units_and_depth_history_range"(pool = %pool, interval = %interval, from = %range.start, to = %range.end), self.history_range("savers", Some(pool), interval, range, |query| async move { self.get_savers_units_and_depth_history(pool, &query).await }))
+	}
+
+	/// Returns the swaps history for every `interval` in `range`, however many there are, fetched page by page as described in [History Ranges](crate#history-ranges).
+	///
+	/// The synth mint and redeem volumes in USD of the meta are always `None`. Midgard reports them for a whole page but not per interval,
+	/// so they can't be recomputed for a range that is partly read from the history store, and they are left out with or without one so that reruns return the same meta.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use chrono::{Duration, Utc};
+	/// use midgard_rs::{Interval, Midgard};
+	///
+	/// # tokio_test::block_on(async {
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
+	///
+	/// // two years of daily intervals, more than a single request can return
+	/// let now = Utc::now();
+	/// let history = midgard.get_swaps_history_range(None, Interval::Day, now - Duration::days(730)..now).await.unwrap();
+	///
+	/// assert!(history.get_intervals().get_swap_intervals().len() > 400);
+	/// # });
+	/// ```
+	///
+	/// # Errors
+	/// Any error returned while fetching one of the pages or reading and writing the history store.
+	pub async fn get_swaps_history_range(&self, pool: Option<&str>, interval: Interval, range: Range<DateTime<Utc>>) -> Result<SwapHistory, MidgardError> {
+		let history = traced!("get_swaps_history_range"(pool = ?pool, interval = %interval, from = %range.start, to = %range.end), self.history_range("swaps", pool, interval, range, |query| async move { self.get_swaps_history(pool, &query).await }));
+		history.map(|mut history: SwapHistory| {
+			history.meta.clear_synth_volumes_usd();
+			history
+		})
+	}
+
+	/// Returns the total value locked history for every `interval` in `range`, however many there are, fetched page by page as described in [History Ranges](crate#history-ranges).
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use chrono::{Duration, Utc};
+	/// use midgard_rs::{Interval, Midgard};
+	///
+	/// # tokio_test::block_on(async {
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
+	///
+	/// // two years of daily intervals, more than a single request can return
+	/// let now = Utc::now();
+	/// let history = midgard.get_total_value_locked_history_range(Interval::Day, now - Duration::days(730)..now).await.unwrap();
+	///
+	/// assert!(history.get_intervals().get_intervals().len() > 400);
+	/// # });
+	/// ```
+	///
+	/// # Errors
+	/// Any error returned while fetching one of the pages or reading and writing the history store.
+	pub async fn get_total_value_locked_history_range(&self, interval: Interval, range: Range<DateTime<Utc>>) -> Result<TVLHistory, MidgardError> {
+		traced!("get_total_value_locked_history_range"(interval = %interval, from = %range.start, to = %range.end), self.history_range("tvl", None, interval, range, |query| async move { self.get_total_value_locked_history(&query).await }))
+	}
+
+	/// Fetches `range` page by page. With a history store configured, stored intervals are used instead of requests, only the windows between them are fetched, and newly fetched closed intervals are stored.
+	async fn history_range<H, F, Fut>(&self, endpoint: &str, pool: Option<&str>, interval: Interval, range: Range<DateTime<Utc>>, fetch: F) -> Result<H, MidgardError>
+	where
+		H: PagedHistory + Clone + Serialize + DeserializeOwned + Send + Sync,
+		F: Fn(HistoryQuery) -> Fut + Sync,
+		Fut: Future<Output = Result<H, MidgardError>>,
+	{
+		let mut stored = match self.config.get_history_store_dir() {
+			Some(dir) => Some(HistoryStore::new(dir).open(endpoint, pool, &interval).await?),
+			None => None,
+		};
+
+		let mut paginator = HistoryPaginator::new(interval, range);
+		while let Some(from) = paginator.next_from() {
+			let Some(stored) = &mut stored else {
+				let Some(query) = paginator.next_query()? else {
+					break;
+				};
+				paginator.push(fetch(query).await?);
+				continue;
+			};
+
+			if let Some(interval) = stored.interval_at(from, paginator.get_to()) {
+				event!(debug, endpoint, %from, "served from the history store");
+				paginator.push(interval);
+				continue;
+			}
+			let Some(query) = paginator.next_query_before(stored.next_start(from))? else {
+				break;
+			};
+			let page = fetch(query).await?;
+			stored.insert(&page, Utc::now()).await?;
+			paginator.push(page);
+		}
+		Ok(paginator.finish())
 	}
 }
 
 #[cfg(test)]
 mod tests {
+	use chrono::TimeZone;
 	use serde_json::json;
 
 	use super::*;
 
+	use crate::test_support::{fixture, MockMidgard, MockResponse};
+
+	#[tokio::test]
+	async fn test_range_uses_history_store() {
+		let day = |start: i64| format!(r#"{{"startTime":"{start}","endTime":"{}","poolsDepth":[],"runePriceUSD":"1.5","totalValuePooled":"100"}}"#, start + 86_400);
+		let page = |starts: &[i64]| format!(r#"{{"intervals":[{}],"meta":{{"startTime":"{}","endTime":"{}","poolsDepth":[],"runePriceUSD":"1.5","totalValuePooled":"100"}}}}"#, starts.iter().map(|start| day(*start)).collect::<Vec<_>>().join(","), starts[0], starts[starts.len() - 1] + 86_400);
+		let server = MockMidgard::start().await;
+		server.set_responses("history/tvl", vec![MockResponse::new(200, &page(&[1_700_006_400, 1_700_092_800])), MockResponse::new(200, &page(&[1_699_920_000])), MockResponse::new(200, &page(&[1_700_179_200]))]);
+		let dir = std::env::temp_dir().join(format!("midgard-rs-history-range-{}", std::process::id()));
+		let mut config = server.get_config();
+		config.set_history_store_dir(Some(dir.clone()));
+		let range = Utc.timestamp_opt(1_700_006_400, 0).unwrap()..Utc.timestamp_opt(1_700_179_200, 0).unwrap();
+
+		let history = Midgard::with_config(config.clone()).get_total_value_locked_history_range(Interval::Day, range.clone()).await.unwrap();
+		assert_eq!(history.get_intervals().get_intervals().len(), 2);
+		assert_eq!(server.get_requests().len(), 1);
+
+		// a rerun is served from disk
+		let history = Midgard::with_config(config.clone()).get_total_value_locked_history_range(Interval::Day, range).await.unwrap();
+		assert_eq!(history.get_intervals().get_intervals().len(), 2);
+		assert_eq!(server.get_requests().len(), 1);
+
+		// a wider range only requests the days around the stored ones
+		let range = Utc.timestamp_opt(1_699_920_000, 0).unwrap()..Utc.timestamp_opt(1_700_265_600, 0).unwrap();
+		let history = Midgard::with_config(config).get_total_value_locked_history_range(Interval::Day, range).await.unwrap();
+		assert_eq!(history.get_intervals().get_intervals().len(), 4);
+		assert_eq!(history.get_meta().get_start_time().timestamp(), 1_699_920_000);
+		assert_eq!(history.get_meta().get_end_time().timestamp(), 1_700_265_600);
+		assert_eq!(server.get_requests().len(), 3);
+		let requests = server.get_requests();
+		assert!(requests.iter().any(|request| request.contains("from=1699920000") && request.contains("to=1700006399")));
+		assert!(requests.iter().any(|request| request.contains("from=1700179200") && request.contains("to=1700265600")));
+
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_swaps_range_meta_is_the_same_with_history_store() {
+		let fixture: SwapHistory = serde_json::from_str(fixture("history/swaps").unwrap()).unwrap();
+		let consistent = PagedHistory::split(fixture).into_iter().reduce(|mut history, next| {
+			PagedHistory::merge(&mut history, next);
+			history
+		});
+		let mut page = serde_json::to_value(consistent.unwrap()).unwrap();
+		page["meta"]["synthMintVolumeUSD"] = json!("121851");
+		page["meta"]["synthRedeemVolumeUSD"] = json!("97485");
+		let server = MockMidgard::start().await;
+		server.set_response("history/swaps", 200, &page.to_string());
+		let dir = std::env::temp_dir().join(format!("midgard-rs-swaps-range-{}", std::process::id()));
+		let mut stored = server.get_config();
+		stored.set_history_store_dir(Some(dir.clone()));
+		let range = Utc.timestamp_opt(1_710_201_600, 0).unwrap()..Utc.timestamp_opt(1_710_460_800, 0).unwrap();
+
+		let fetched = Midgard::with_config(server.get_config()).get_swaps_history_range(None, Interval::Day, range.clone()).await.unwrap();
+		Midgard::with_config(stored.clone()).get_swaps_history_range(None, Interval::Day, range.clone()).await.unwrap();
+		let replayed = Midgard::with_config(stored).get_swaps_history_range(None, Interval::Day, range).await.unwrap();
+
+		assert_eq!(server.get_requests().len(), 2);
+		assert_eq!(fetched.get_meta().get_synth_mint_volume_usd(), &None);
+		assert_eq!(serde_json::to_value(fetched.get_meta()).unwrap(), serde_json::to_value(replayed.get_meta()).unwrap());
+
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
 	#[tokio::test]
 	async fn test_get_depth_and_price_history() {
 		// Create a new instance of Midgard
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		// Get depth & price history
-		let depth_history = midgard.get_depth_and_price_history("BTC.BTC", Some(Interval::Day), Some(10), None, None).await.unwrap();
+		let depth_history = midgard.get_depth_and_price_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 
 		println!("depth history: {}", json!(depth_history));
 
@@ -447,19 +727,20 @@ mod tests {
 	#[tokio::test]
 	async fn test_get_depth_and_price_history_pagination() {
 		// Create a new instance of Midgard
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		// Get depth & price history
-		let depth_history = midgard.get_depth_and_price_history("BTC.BTC", Some(Interval::Day), Some(10), None, None).await.unwrap();
+		let depth_history = midgard.get_depth_and_price_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 
 		println!("depth history: {}", json!(depth_history));
 
 		assert!(!depth_history.get_intervals().is_empty());
 
 		// Get the end time
-		let end_time = depth_history.get_meta().get_end_time().timestamp() as u64;
+		let end_time = *depth_history.get_meta().get_end_time();
 
-		let depth_history = midgard.get_depth_and_price_history("BTC.BTC", Some(Interval::Day), Some(10), None, Some(end_time)).await.unwrap();
+		let depth_history = midgard.get_depth_and_price_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).from(end_time).build().unwrap()).await.unwrap();
 
 		println!("depth history: {}", json!(depth_history));
 
@@ -469,10 +750,11 @@ mod tests {
 	#[tokio::test]
 	async fn test_get_earnings_history() {
 		// Create a new instance of Midgard
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		// Get earnings history
-		let earnings_history = midgard.get_earnings_history(Some(Interval::Day), Some(10), None, None).await.unwrap();
+		let earnings_history = midgard.get_earnings_history(&HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 
 		println!("earnings history: {}", json!(earnings_history));
 
@@ -482,19 +764,20 @@ mod tests {
 	#[tokio::test]
 	async fn test_get_earnings_history_pagination() {
 		// Create a new instance of Midgard
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		// Get earnings history
-		let earnings_history = midgard.get_earnings_history(Some(Interval::Day), Some(10), None, None).await.unwrap();
+		let earnings_history = midgard.get_earnings_history(&HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 
 		println!("earnings history: {}", json!(earnings_history));
 
 		assert!(!earnings_history.get_intervals().is_empty());
 
 		// Get the end time
-		let end_time = earnings_history.get_meta().get_end_time().timestamp() as u64;
+		let end_time = *earnings_history.get_meta().get_end_time();
 
-		let earnings_history = midgard.get_earnings_history(Some(Interval::Day), Some(10), None, Some(end_time)).await.unwrap();
+		let earnings_history = midgard.get_earnings_history(&HistoryQuery::builder().interval(Interval::Day).count(10).from(end_time).build().unwrap()).await.unwrap();
 
 		println!("earnings history: {}", json!(earnings_history));
 
@@ -504,10 +787,11 @@ mod tests {
 	#[tokio::test]
 	async fn test_get_liquidity_change_history() {
 		// Create a new instance of Midgard
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		// Get liquidity change history
-		let liquidity_change_history = midgard.get_liquidity_change_history("BTC.BTC", Some(Interval::Day), Some(10), None, None).await.unwrap();
+		let liquidity_change_history = midgard.get_liquidity_change_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 
 		println!("liquidity change history: {}", json!(liquidity_change_history));
 
@@ -517,19 +801,20 @@ mod tests {
 	#[tokio::test]
 	async fn test_get_liquidity_change_history_pagination() {
 		// Create a new instance of Midgard
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		// Get liquidity change history
-		let liquidity_change_history = midgard.get_liquidity_change_history("BTC.BTC", Some(Interval::Day), Some(10), None, None).await.unwrap();
+		let liquidity_change_history = midgard.get_liquidity_change_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 
 		println!("liquidity change history: {}", json!(liquidity_change_history));
 
 		assert!(!liquidity_change_history.get_intervals().is_empty());
 
 		// Get the end time
-		let end_time = liquidity_change_history.get_meta().get_end_time().timestamp() as u64;
+		let end_time = *liquidity_change_history.get_meta().get_end_time();
 
-		let liquidity_change_history = midgard.get_liquidity_change_history("BTC.BTC", Some(Interval::Day), Some(10), None, Some(end_time)).await.unwrap();
+		let liquidity_change_history = midgard.get_liquidity_change_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).from(end_time).build().unwrap()).await.unwrap();
 
 		println!("liquidity change history: {}", json!(liquidity_change_history));
 
@@ -539,10 +824,11 @@ mod tests {
 	#[tokio::test]
 	async fn test_get_savers_units_and_depth_history() {
 		// Create a new instance of Midgard
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		// Get savers units and depth history
-		let savers_history = midgard.get_savers_units_and_depth_history("BTC.BTC", Some(Interval::Day), Some(10), None, None).await.unwrap();
+		let savers_history = midgard.get_savers_units_and_depth_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 
 		println!("savers history: {}", json!(savers_history));
 
@@ -552,19 +838,20 @@ mod tests {
 	#[tokio::test]
 	async fn test_get_savers_units_and_depth_history_pagination() {
 		// Create a new instance of Midgard
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		// Get savers units and depth history
-		let savers_history = midgard.get_savers_units_and_depth_history("BTC.BTC", Some(Interval::Day), Some(10), None, None).await.unwrap();
+		let savers_history = midgard.get_savers_units_and_depth_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 
 		println!("savers history: {}", json!(savers_history));
 
 		assert!(!savers_history.get_intervals().is_empty());
 
 		// Get the end time
-		let end_time = savers_history.get_meta().get_end_time().timestamp() as u64;
+		let end_time = *savers_history.get_meta().get_end_time();
 
-		let savers_history = midgard.get_savers_units_and_depth_history("BTC.BTC", Some(Interval::Day), Some(10), None, Some(end_time)).await.unwrap();
+		let savers_history = midgard.get_savers_units_and_depth_history("BTC.BTC", &HistoryQuery::builder().interval(Interval::Day).count(10).from(end_time).build().unwrap()).await.unwrap();
 
 		println!("savers history: {}", json!(savers_history));
 
@@ -574,9 +861,10 @@ mod tests {
 	#[tokio::test]
 	async fn test_get_swaps_history() {
 		// Create a new instance of Midgard
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 		// Get swaps history
-		let swaps_history = midgard.get_swaps_history(None, Some(Interval::Day), Some(10), None, None).await.unwrap();
+		let swaps_history = midgard.get_swaps_history(None, &HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 		println!("swaps history: {}", json!(swaps_history));
 		assert!(!swaps_history.get_intervals().is_empty());
 	}
@@ -584,15 +872,16 @@ mod tests {
 	#[tokio::test]
 	async fn test_get_swaps_history_pagination() {
 		// Create a new instance of Midgard
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 		// Get swaps history
-		let swaps_history = midgard.get_swaps_history(None, Some(Interval::Day), Some(10), None, None).await.unwrap();
+		let swaps_history = midgard.get_swaps_history(None, &HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 		println!("swaps history: {}", json!(swaps_history));
 		assert!(!swaps_history.get_intervals().is_empty());
 
 		// Get the end time
-		let end_time = swaps_history.get_meta().get_end_time().timestamp() as u64;
-		let swaps_history = midgard.get_swaps_history(None, Some(Interval::Day), Some(10), None, Some(end_time)).await.unwrap();
+		let end_time = *swaps_history.get_meta().get_end_time();
+		let swaps_history = midgard.get_swaps_history(None, &HistoryQuery::builder().interval(Interval::Day).count(10).from(end_time).build().unwrap()).await.unwrap();
 		println!("swaps history: {}", json!(swaps_history));
 		assert!(!swaps_history.get_intervals().is_empty());
 	}
@@ -600,9 +889,10 @@ mod tests {
 	#[tokio::test]
 	async fn test_get_total_value_locked_history() {
 		// Create a new instance of Midgard
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 		// Get total value locked history
-		let tvl_history = midgard.get_total_value_locked_history(Some(Interval::Day), Some(10), None, None).await.unwrap();
+		let tvl_history = midgard.get_total_value_locked_history(&HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 		println!("tvl history: {}", json!(tvl_history));
 		assert!(!tvl_history.get_intervals().is_empty());
 	}
@@ -610,15 +900,16 @@ mod tests {
 	#[tokio::test]
 	async fn test_get_total_value_locked_history_pagination() {
 		// Create a new instance of Midgard
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 		// Get total value locked history
-		let tvl_history = midgard.get_total_value_locked_history(Some(Interval::Day), Some(10), None, None).await.unwrap();
+		let tvl_history = midgard.get_total_value_locked_history(&HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap()).await.unwrap();
 		println!("tvl history: {}", json!(tvl_history));
 		assert!(!tvl_history.get_intervals().is_empty());
 
 		// Get the end time
-		let end_time = tvl_history.get_meta().get_end_time().timestamp() as u64;
-		let tvl_history = midgard.get_total_value_locked_history(Some(Interval::Day), Some(10), None, Some(end_time)).await.unwrap();
+		let end_time = *tvl_history.get_meta().get_end_time();
+		let tvl_history = midgard.get_total_value_locked_history(&HistoryQuery::builder().interval(Interval::Day).count(10).from(end_time).build().unwrap()).await.unwrap();
 		println!("tvl history: {}", json!(tvl_history));
 		assert!(!tvl_history.get_intervals().is_empty());
 	}
@@ -1,7 +1,5 @@
-use anyhow::Result;
-use chrono::Utc;
-
-use crate::{api_get_member_details, api_get_member_list, MemberDetails, MemberList, Midgard};
+use crate::{api_get_member_details, api_get_member_list, MemberDetails, MemberList, Midgard, MidgardError};
+use crate::trace::traced;
 
 impl Midgard {
 	/// Returns an array of statistics for all the liquidity providers associated with a given member address.
@@ -27,7 +25,9 @@ impl Midgard {
 	/// use rand::prelude::*;
 	///
 	/// # tokio_test::block_on(async {
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	///
 	/// // get random pool members list
 	/// let pool_list = midgard.get_pool_list(None, None).await.unwrap();
@@ -50,14 +50,10 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-	pub async fn get_member_details(&mut self, address: &[String], show_savers: bool) -> Result<MemberDetails> {
-		// Wait for rate limit timer
-		self.sleep_until_ok_to_call().await;
-
+	pub async fn get_member_details(&self, address: &[String], show_savers: bool) -> Result<MemberDetails, MidgardError> {
 		let address = address.join(",");
 
-		self.set_last_call(Utc::now());
-		api_get_member_details(self.get_config().get_base_url(), &address, show_savers).await
+		traced!("get_member_details"(address = %address, show_savers), api_get_member_details(self, &address, show_savers))
 	}
 
 	/// Returns an array containing the addresses for all pool members. Addresses are only shown once. If there's both a RUNE address and an asset address for a member, only the RUNE address will be shown.
@@ -73,7 +69,9 @@ impl Midgard {
 	/// use rand::prelude::*;
 	///
 	/// # tokio_test::block_on(async {
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	///
 	/// // get random pool
 	/// let pool_list = midgard.get_pool_list(None, None).await.unwrap();
@@ -89,12 +87,8 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-	pub async fn get_member_list(&mut self, pool: Option<String>) -> Result<MemberList> {
-		// Wait for rate limit timer
-		self.sleep_until_ok_to_call().await;
-
-		self.set_last_call(Utc::now());
-		api_get_member_list(self.get_config().get_base_url(), pool).await
+	pub async fn get_member_list(&self, pool: Option<String>) -> Result<MemberList, MidgardError> {
+		traced!("get_member_list"(pool = ?pool), api_get_member_list(self, pool))
 	}
 }
 
@@ -103,11 +97,12 @@ mod tests {
 	use rand::prelude::*;
 	use serde_json::json;
 
-	use super::*;
+	use crate::test_support::MockMidgard;
 
 	#[tokio::test]
 	async fn test_get_member_details() {
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		// get random pool members list
 		let pool_list = midgard.get_pool_list(None, None).await.unwrap();
@@ -129,7 +124,8 @@ mod tests {
 
 	#[tokio::test]
 	async fn test_get_member_list() {
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		// get random pool
 		let pool_list = midgard.get_pool_list(None, None).await.unwrap();
@@ -1,8 +1,6 @@
-use anyhow::Result;
-use chrono::Utc;
-
 use crate::Midgard;
-use crate::{api_get_network_data, NetworkData};
+use crate::{api_get_network_data, MidgardError, NetworkData};
+use crate::trace::traced;
 
 impl Midgard {
 	/// Returns an object containing Network data
@@ -10,7 +8,9 @@ impl Midgard {
 	/// ```rust
 	/// use midgard_rs::Midgard;
 	/// # tokio_test::block_on(async {
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	/// let network_data = midgard.get_network_data().await.unwrap();
 	///
 	/// assert!(!network_data.get_active_bonds().is_empty());
@@ -19,12 +19,8 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-	pub async fn get_network_data(&mut self) -> Result<NetworkData> {
-		// Wait for rate limit timer
-		self.sleep_until_ok_to_call().await;
-
-		self.set_last_call(Utc::now());
-		api_get_network_data(self.get_config().get_base_url()).await
+	pub async fn get_network_data(&self) -> Result<NetworkData, MidgardError> {
+		traced!("get_network_data", api_get_network_data(self))
 	}
 }
 
@@ -32,11 +28,12 @@ impl Midgard {
 mod tests {
 	use serde_json::json;
 
-	use super::*;
+	use crate::test_support::MockMidgard;
 
 	#[tokio::test]
 	async fn test_get_network_data() {
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 		let network_data = midgard.get_network_data().await.unwrap();
 
 		println!("network data: {}", json!(network_data).to_string());
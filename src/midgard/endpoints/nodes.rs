@@ -1,8 +1,6 @@
-use anyhow::Result;
-use chrono::Utc;
-
 use crate::Midgard;
-use crate::{api_get_node_list, NodeList};
+use crate::{api_get_node_list, MidgardError, NodeList};
+use crate::trace::traced;
 
 impl Midgard {
 	/// Returns a list of Node public keys and adresses.
@@ -10,7 +8,9 @@ impl Midgard {
 	/// ```rust
 	/// use midgard_rs::Midgard;
 	/// # tokio_test::block_on(async {
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	/// let node_list = midgard.get_node_list().await.unwrap();
 	///
 	/// assert!(!node_list.get_nodes().is_empty());
@@ -19,12 +19,8 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-	pub async fn get_node_list(&mut self) -> Result<NodeList> {
-		// Wait for rate limit timer
-		self.sleep_until_ok_to_call().await;
-
-		self.set_last_call(Utc::now());
-		api_get_node_list(self.get_config().get_base_url()).await
+	pub async fn get_node_list(&self) -> Result<NodeList, MidgardError> {
+		traced!("get_node_list", api_get_node_list(self))
 	}
 }
 
@@ -32,11 +28,12 @@ impl Midgard {
 mod tests {
 	use serde_json::json;
 
-	use super::*;
+	use crate::test_support::MockMidgard;
 
 	#[tokio::test]
 	async fn test_get_node_list() {
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 		let node_list = midgard.get_node_list().await.unwrap();
 
 		println!("node list: {}", json!(node_list).to_string());
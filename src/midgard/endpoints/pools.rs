@@ -1,7 +1,5 @@
-use anyhow::Result;
-use chrono::Utc;
-
-use crate::{api_get_details_of_pool, api_get_pool_list, api_get_known_pool_list, api_get_statistics_of_pool, Midgard, KnownPoolList, Pool, PoolList, PoolStatus, PoolStatistics, TimePeriod};
+use crate::{api_get_details_of_pool, api_get_pool_list, api_get_known_pool_list, api_get_statistics_of_pool, Midgard, KnownPoolList, MidgardError, Pool, PoolList, PoolStatus, PoolStatistics, TimePeriod};
+use crate::trace::traced;
 
 impl Midgard {
 	/// Returns an array containing details for a set of pools.
@@ -11,7 +9,9 @@ impl Midgard {
 	/// use midgard_rs::PoolStatus;
 	/// use midgard_rs::TimePeriod;
 	/// # tokio_test::block_on(async {
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	///
 	/// let pool_list = midgard.get_pool_list(None, None).await.unwrap();
 	/// assert!(!pool_list.get_pools().is_empty());
@@ -20,12 +20,8 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-	pub async fn get_pool_list(&mut self, status: Option<PoolStatus>, period: Option<TimePeriod>) -> Result<PoolList> {
-		// Wait for rate limit timer
-		self.sleep_until_ok_to_call().await;
-
-		self.set_last_call(Utc::now());
-		api_get_pool_list(self.get_config().get_base_url(), status, period).await
+	pub async fn get_pool_list(&self, status: Option<PoolStatus>, period: Option<TimePeriod>) -> Result<PoolList, MidgardError> {
+		traced!("get_pool_list"(status = ?status, period = ?period), api_get_pool_list(self, status, period))
 	}
 
 	/// Returns details of the pool: depths, price, 24h volume, APY.
@@ -35,7 +31,9 @@ impl Midgard {
 	/// use midgard_rs::TimePeriod;
 	/// use rand::prelude::*;
 	/// # tokio_test::block_on(async {
-	/// let mut midgard = Midgard::new();
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
 	///
         /// // Get a random pool
 	/// let pool_list = midgard.get_pool_list(None, None).await.unwrap();
@@ -51,12 +49,8 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-	pub async fn get_details_of_pool(&mut self, pool: &str, period: Option<TimePeriod>) -> Result<Pool> {
-		// Wait for rate limit timer
-		self.sleep_until_ok_to_call().await;
-
-		self.set_last_call(Utc::now());
-		api_get_details_of_pool(self.get_config().get_base_url(), pool, period).await
+	pub async fn get_details_of_pool(&self, pool: &str, period: Option<TimePeriod>) -> Result<Pool, MidgardError> {
+		traced!("get_details_of_pool"(pool = %pool, period = ?period), api_get_details_of_pool(self, pool, period))
 	}
 
         /// Returns an object with known pools and their statuses.
@@ -65,7 +59,9 @@ impl Midgard {
         /// use midgard_rs::Midgard;
         /// use midgard_rs::PoolStatus;
         /// # tokio_test::block_on(async {
-        /// let mut midgard = Midgard::new();
+        /// let midgard = Midgard::new();
+        /// # let server = midgard_rs::test_support::MockMidgard::start().await;
+        /// # let midgard = server.get_midgard();
         /// 
         /// let known_pool_list = midgard.get_known_pool_list().await.unwrap();
         /// assert!(!known_pool_list.into_iter().collect::<Vec<(String, PoolStatus)>>().is_empty());
@@ -74,12 +70,8 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-        pub async fn get_known_pool_list(&mut self) -> Result<KnownPoolList> {
-                // Wait for rate limit timer
-                self.sleep_until_ok_to_call().await;
-
-                self.set_last_call(Utc::now());
-                api_get_known_pool_list(self.get_config().get_base_url()).await
+        pub async fn get_known_pool_list(&self) -> Result<KnownPoolList, MidgardError> {
+                traced!("get_known_pool_list", api_get_known_pool_list(self))
         }
 
         /// Statistics about the pool. The description of the fields have pointers about the corresponding v2/history location. Visit the history endpoint for drilldowns.
@@ -89,7 +81,9 @@ impl Midgard {
         /// use midgard_rs::TimePeriod;
         /// use rand::prelude::*;
         /// # tokio_test::block_on(async {
-        /// let mut midgard = Midgard::new();
+        /// let midgard = Midgard::new();
+        /// # let server = midgard_rs::test_support::MockMidgard::start().await;
+        /// # let midgard = server.get_midgard();
         /// 
         /// // Get a random pool
         /// let pool_list = midgard.get_pool_list(None, None).await.unwrap();
@@ -105,12 +99,8 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-        pub async fn get_statistics_of_pool(&mut self, pool: &str, period: Option<TimePeriod>) -> Result<PoolStatistics> {
-                // Wait for rate limit timer
-                self.sleep_until_ok_to_call().await;
-
-                self.set_last_call(Utc::now());
-                api_get_statistics_of_pool(self.get_config().get_base_url(), pool, period).await
+        pub async fn get_statistics_of_pool(&self, pool: &str, period: Option<TimePeriod>) -> Result<PoolStatistics, MidgardError> {
+                traced!("get_statistics_of_pool"(pool = %pool, period = ?period), api_get_statistics_of_pool(self, pool, period))
         }
 
 }
@@ -121,10 +111,12 @@ mod tests {
 	use serde_json::json;
 
 	use super::*;
+	use crate::test_support::MockMidgard;
 
 	#[tokio::test]
 	async fn test_get_pool_list() {
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		let pool_list = midgard.get_pool_list(None, None).await.unwrap();
 		println!("{}", json!(pool_list.get_pools()));
@@ -133,7 +125,8 @@ mod tests {
 
 	#[tokio::test]
 	async fn test_get_simplified_assets() {
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		let pool_list = midgard.get_pool_list(None, None).await.unwrap();
 		println!("{}", json!(pool_list.get_simplified_assets()));
@@ -142,7 +135,8 @@ mod tests {
 
 	#[tokio::test]
 	async fn test_get_pool_list_with_status() {
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		let status = Some(PoolStatus::Available);
 		let period = None;
@@ -154,7 +148,8 @@ mod tests {
 
 	#[tokio::test]
 	async fn test_get_pool_list_with_period() {
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		let status = None;
 		let period = Some(TimePeriod::SevenDays);
@@ -166,7 +161,8 @@ mod tests {
 
 	#[tokio::test]
 	async fn test_get_pool_list_with_status_and_period() {
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		let status = Some(PoolStatus::Available);
 		let period = Some(TimePeriod::FourteenDays);
@@ -178,7 +174,8 @@ mod tests {
 
 	#[tokio::test]
 	async fn test_get_details_of_pool() {
-		let mut midgard = Midgard::new();
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
 
 		let pool_list = midgard.get_pool_list(None, None).await.unwrap();
 		let random_usize = thread_rng().gen_range(0..pool_list.get_pools().len());
@@ -193,7 +190,8 @@ mod tests {
 
         #[tokio::test]
         async fn test_get_known_pool_list() {
-                let mut midgard = Midgard::new();
+                let server = MockMidgard::start().await;
+                let midgard = server.get_midgard();
 
                 let known_pool_list = midgard.get_known_pool_list().await.unwrap();
                 println!("{}", json!(known_pool_list));
@@ -202,7 +200,8 @@ mod tests {
 
         #[tokio::test]
         async fn test_get_statistics_of_pool() {
-                let mut midgard = Midgard::new();
+                let server = MockMidgard::start().await;
+                let midgard = server.get_midgard();
 
                 // Get a random pool
                 let pool_list = midgard.get_pool_list(None, None).await.unwrap();
@@ -1,8 +1,6 @@
-use anyhow::Result;
-use chrono::Utc;
-
 use crate::Midgard;
-use crate::{api_get_savers_details, SaversDetails};
+use crate::{api_get_savers_details, MidgardError, SaversDetails};
+use crate::trace::traced;
 
 impl Midgard {
         ///Returns an array of statistics for all the savers associated with a given member address. Query can also be multiple addresses should be seperated by comma (',').
@@ -11,7 +9,9 @@ impl Midgard {
         /// use midgard_rs::Midgard;
         /// use midgard_rs::SaversDetails;
         /// # tokio_test::block_on(async {
-        /// let mut midgard = Midgard::new();
+        /// let midgard = Midgard::new();
+        /// # let server = midgard_rs::test_support::MockMidgard::start().await;
+        /// # let midgard = server.get_midgard();
         /// let address = vec![
         ///         "bnb1jxfh2g85q3v0tdq56fnevx6xcxtcnhtsmcu64m".to_string(),
         ///         "bc1qcxssye4j6730h7ehgega3gyykkuwgdgmmpu62n".to_string(),
@@ -24,12 +24,8 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-        pub async fn get_savers_details(&mut self, address: &[String]) -> Result<SaversDetails> {
-                // Wait for rate limit timer
-                self.sleep_until_ok_to_call().await;
-
-                self.set_last_call(Utc::now());
-                api_get_savers_details(self.get_config().get_base_url(), address).await
+        pub async fn get_savers_details(&self, address: &[String]) -> Result<SaversDetails, MidgardError> {
+                traced!("get_savers_details"(address = ?address), api_get_savers_details(self, address))
         }
 }
 
@@ -37,11 +33,12 @@ impl Midgard {
 mod tests {
         use serde_json::json;
 
-        use super::*;
+        use crate::test_support::MockMidgard;
 
         #[tokio::test]
         async fn test_get_savers_details() {
-                let mut midgard = Midgard::new();
+                let server = MockMidgard::start().await;
+                let midgard = server.get_midgard();
                 let address = vec![
                         "bnb1jxfh2g85q3v0tdq56fnevx6xcxtcnhtsmcu64m".to_string(),
                         "bc1qcxssye4j6730h7ehgega3gyykkuwgdgmmpu62n".to_string(),
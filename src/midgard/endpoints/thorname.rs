@@ -1,8 +1,6 @@
-use anyhow::Result;
-use chrono::Utc;
-
 use crate::Midgard;
-use crate::{api_get_thorname_details, api_get_thorname_owner, api_get_thorname_reverse_lookup, ThornameDetails, ThornameOwner, ThornameReverseLookup};
+use crate::{api_get_thorname_details, api_get_thorname_owner, api_get_thorname_reverse_lookup, MidgardError, ThornameDetails, ThornameOwner, ThornameReverseLookup};
+use crate::trace::traced;
 
 impl Midgard {
         /// Returns an array of chains and their addresses associated with the given `THORName`.
@@ -11,7 +9,9 @@ impl Midgard {
         /// use midgard_rs::Midgard;
         /// 
         /// # tokio_test::block_on(async {
-        /// let mut midgard = Midgard::new();
+        /// let midgard = Midgard::new();
+        /// # let server = midgard_rs::test_support::MockMidgard::start().await;
+        /// # let midgard = server.get_midgard();
         /// let name = "thorchain";
         /// 
         /// let thorname_details = midgard.get_thorname_details(&name).await.unwrap();
@@ -21,12 +21,8 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-        pub async fn get_thorname_details(&mut self, name: &str) -> Result<ThornameDetails> {
-                // Wait for rate limit timer
-                self.sleep_until_ok_to_call().await;
-
-                self.set_last_call(Utc::now());
-                api_get_thorname_details(self.get_config().get_base_url(), name).await
+        pub async fn get_thorname_details(&self, name: &str) -> Result<ThornameDetails, MidgardError> {
+                traced!("get_thorname_details"(name = %name), api_get_thorname_details(self, name))
         }
 
         /// Returns an array of `THORNames` owned by the address. The address is not necessarily an associated address for those thornames.
@@ -35,7 +31,9 @@ impl Midgard {
         /// use midgard_rs::Midgard;
         /// 
         /// # tokio_test::block_on(async {
-        /// let mut midgard = Midgard::new();
+        /// let midgard = Midgard::new();
+        /// # let server = midgard_rs::test_support::MockMidgard::start().await;
+        /// # let midgard = server.get_midgard();
         /// let address = "thor18w0hsdru75ug0x4uvamgjn6ghlu43mr4dcypq9";
         /// let thorname_owner = midgard.get_thorname_owner(&address).await.unwrap();
         /// assert!(!thorname_owner.get_thorname_owner().is_empty());
@@ -44,12 +42,8 @@ impl Midgard {
         /// 
         /// # Errors
         /// todo
-        pub async fn get_thorname_owner(&mut self, address: &str) -> Result<ThornameOwner> {
-                // Wait for rate limit timer
-                self.sleep_until_ok_to_call().await;
-
-                self.set_last_call(Utc::now());
-                api_get_thorname_owner(self.get_config().get_base_url(), address).await
+        pub async fn get_thorname_owner(&self, address: &str) -> Result<ThornameOwner, MidgardError> {
+                traced!("get_thorname_owner"(address = %address), api_get_thorname_owner(self, address))
         }
 
         /// Returns an array of `THORNames` associated with the given address
@@ -58,7 +52,9 @@ impl Midgard {
         /// use midgard_rs::Midgard;
         /// 
         /// # tokio_test::block_on(async {
-        /// let mut midgard = Midgard::new();
+        /// let midgard = Midgard::new();
+        /// # let server = midgard_rs::test_support::MockMidgard::start().await;
+        /// # let midgard = server.get_midgard();
         /// let address = "thor18w0hsdru75ug0x4uvamgjn6ghlu43mr4dcypq9";
         /// let thorname_owner = midgard.get_thorname_owner(&address).await.unwrap();
         /// assert!(!thorname_owner.get_thorname_owner().is_empty());
@@ -66,12 +62,8 @@ impl Midgard {
         /// ```
         /// # Errors
         /// todo
-        pub async fn get_thorname_reverse_lookup(&mut self, address: &str) -> Result<ThornameReverseLookup> {
-                // Wait for rate limit timer
-                self.sleep_until_ok_to_call().await;
-
-                self.set_last_call(Utc::now());
-                api_get_thorname_reverse_lookup(self.get_config().get_base_url(), address).await
+        pub async fn get_thorname_reverse_lookup(&self, address: &str) -> Result<ThornameReverseLookup, MidgardError> {
+                traced!("get_thorname_reverse_lookup"(address = %address), api_get_thorname_reverse_lookup(self, address))
         }
 }
 
@@ -79,11 +71,12 @@ impl Midgard {
 mod tests {
         use serde_json::json;
 
-        use super::*;
+        use crate::test_support::MockMidgard;
 
         #[tokio::test]
         async fn test_get_thorname_details() {
-                let mut midgard = Midgard::new();
+                let server = MockMidgard::start().await;
+                let midgard = server.get_midgard();
                 let name = "thorchain";
 
                 let thorname_details = midgard.get_thorname_details(&name).await.unwrap();
@@ -93,7 +86,8 @@ mod tests {
 
         #[tokio::test]
         async fn test_get_thorname_owner() {
-                let mut midgard = Midgard::new();
+                let server = MockMidgard::start().await;
+                let midgard = server.get_midgard();
                 let address = "thor18w0hsdru75ug0x4uvamgjn6ghlu43mr4dcypq9";
 
                 let thorname_owner = midgard.get_thorname_owner(&address).await.unwrap();
@@ -103,7 +97,8 @@ mod tests {
 
         #[tokio::test]
         async fn test_get_thorname_reverse_lookup() {
-                let mut midgard = Midgard::new();
+                let server = MockMidgard::start().await;
+                let midgard = server.get_midgard();
                 let address = "thor18w0hsdru75ug0x4uvamgjn6ghlu43mr4dcypq9";
 
                 let thorname_reverse_lookup = midgard.get_thorname_reverse_lookup(&address).await.unwrap();
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+/// Controls how the client fails over between several Midgard base urls.
+///
+/// Requests go to the first healthy base url in the configured order. An instance is marked unhealthy when a request to it fails with a connection error, a timeout or a 5xx status,
+/// or when its `/health` reports it is not in sync or its scanner lags the most advanced instance by more than `max_scanner_lag` blocks.
+/// With more than one base url configured every instance is probed again every `probe_interval_ms` in the background, and becomes eligible again once it reports healthy. Probes use the configured read and total timeouts, a probe that times out marks the instance unreachable.
+///
+/// `routing` selects how healthy instances are ordered, see `Routing`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct FailoverPolicy {
+	probe_interval_ms: u64,
+	/// Number of blocks an instance's `scannerHeight` may lag the highest one seen before it is considered unhealthy.
+	max_scanner_lag: u64,
+	routing: Routing,
+}
+
+impl FailoverPolicy {
+	#[must_use]
+	pub const fn new(probe_interval_ms: u64, max_scanner_lag: u64) -> Self {
+		Self { probe_interval_ms, max_scanner_lag, routing: Routing::Ordered }
+	}
+
+	#[must_use]
+	pub const fn get_probe_interval_ms(&self) -> u64 {
+		self.probe_interval_ms
+	}
+
+	#[must_use]
+	pub const fn get_max_scanner_lag(&self) -> u64 {
+		self.max_scanner_lag
+	}
+
+	#[must_use]
+	pub const fn get_routing(&self) -> Routing {
+		self.routing
+	}
+
+	pub const fn set_probe_interval_ms(&mut self, probe_interval_ms: u64) {
+		self.probe_interval_ms = probe_interval_ms;
+	}
+
+	pub const fn set_max_scanner_lag(&mut self, max_scanner_lag: u64) {
+		self.max_scanner_lag = max_scanner_lag;
+	}
+
+	pub const fn set_routing(&mut self, routing: Routing) {
+		self.routing = routing;
+	}
+}
+
+impl Default for FailoverPolicy {
+	fn default() -> Self {
+		Self { probe_interval_ms: 30_000, max_scanner_lag: 100, routing: Routing::Ordered }
+	}
+}
+
+/// How requests are spread over the healthy Midgard instances.
+///
+/// # Example
+///
+/// ```rust
+/// use midgard_rs::{Configuration, FailoverPolicy, Routing};
+///
+/// let mut policy = FailoverPolicy::default();
+/// // read from the most up-to-date instance, accept instances at most 5 blocks behind it when it fails
+/// policy.set_routing(Routing::HighestCommitted { max_lag: 5 });
+///
+/// let mut config = Configuration::default();
+/// config.set_base_urls(vec!["https://midgard.ninerealms.com/v2/".to_string(), "https://midgard.thorchain.liquify.com/v2/".to_string()]);
+/// config.set_failover_policy(policy);
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Routing {
+	/// Requests go to the first healthy base url in the configured order.
+	#[default]
+	Ordered,
+	/// Requests go to the instance with the highest `lastCommitted` height reported by `/health`, which is polled on every instance.
+	///
+	/// Instances more than `max_lag` blocks behind the highest one are skipped, and so are instances behind the height of the previous successful read:
+	/// consecutive calls through a `Midgard` instance and its clones never go back in height.
+	/// When no instance has caught up with the previous read, all instances are probed again right away and the request fails with `MidgardError::Inconsistent` if none qualifies.
+	HighestCommitted { max_lag: u64 },
+}
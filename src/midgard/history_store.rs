@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::midgard::paginator::PagedHistory;
+use crate::runtime;
+use crate::{Interval, MidgardError};
+
+/// Time after which an interval is considered final. Younger intervals may still change while Midgard catches up with the chain.
+const SETTLE_TIME: TimeDelta = TimeDelta::hours(1);
+
+/// Closed history intervals stored on disk, one JSON file per (endpoint, pool, interval) series.
+///
+/// Every interval is stored on its own under its `startTime`, as the single-interval page Midgard returns for it.
+/// Pages fetched over different windows therefore add up, and the windows still missing from a range follow from the intervals already stored.
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+	dir: PathBuf,
+}
+
+/// The stored intervals of one series, loaded into memory.
+#[derive(Debug)]
+pub struct StoredSeries {
+	path: PathBuf,
+	intervals: BTreeMap<i64, Value>,
+}
+
+impl HistoryStore {
+	pub fn new(dir: &Path) -> Self {
+		Self { dir: dir.to_path_buf() }
+	}
+
+	/// Loads the intervals stored for a series. A missing file is an empty series.
+	pub async fn open(&self, endpoint: &str, pool: Option<&str>, interval: &Interval) -> Result<StoredSeries, MidgardError> {
+		let path = self.dir.join(format!("{endpoint}-{}-{interval}.json", pool.unwrap_or("all").replace(['/', '\\'], "~")));
+		let intervals = match runtime::read(&path).await {
+			Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| store_error(&path, io::Error::new(io::ErrorKind::InvalidData, e)))?,
+			Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+			Err(e) => return Err(store_error(&path, e)),
+		};
+		Ok(StoredSeries { path, intervals })
+	}
+}
+
+impl StoredSeries {
+	/// Returns the stored interval covering `at` as a single-interval page, if it ends within `..=to`.
+	pub fn interval_at<H: PagedHistory + DeserializeOwned>(&self, at: DateTime<Utc>, to: DateTime<Utc>) -> Option<H> {
+		let (_, interval) = self.intervals.range(..=at.timestamp()).next_back()?;
+		let interval: H = serde_json::from_value(interval.clone()).ok()?;
+		(interval.end_time() > at && interval.end_time() <= to).then_some(interval)
+	}
+
+	/// Returns the start of the first interval stored after `at`.
+	pub fn next_start(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+		let (start, _) = self.intervals.range(at.timestamp() + 1..).next()?;
+		DateTime::from_timestamp(*start, 0)
+	}
+
+	/// Stores every closed interval of a fetched page under its start time, and writes the series back to disk if any of them is new.
+	/// Intervals ending less than `SETTLE_TIME` before `now` are left out, they are fetched again next time.
+	pub async fn insert<H: PagedHistory + Clone + Serialize + Sync>(&mut self, page: &H, now: DateTime<Utc>) -> Result<(), MidgardError> {
+		let mut changed = false;
+		for interval in page.clone().split() {
+			let start = interval.start_time().timestamp();
+			if interval.end_time() > now - SETTLE_TIME || self.intervals.contains_key(&start) {
+				continue;
+			}
+			let interval = serde_json::to_value(&interval).map_err(|e| store_error(&self.path, io::Error::new(io::ErrorKind::InvalidData, e)))?;
+			self.intervals.insert(start, interval);
+			changed = true;
+		}
+		if !changed {
+			return Ok(());
+		}
+		self.save().await
+	}
+
+	/// Writes to a temporary file first, so an interrupted write never leaves a truncated series behind.
+	async fn save(&self) -> Result<(), MidgardError> {
+		let bytes = serde_json::to_vec(&self.intervals).map_err(|e| store_error(&self.path, io::Error::new(io::ErrorKind::InvalidData, e)))?;
+		let tmp = self.path.with_extension("json.tmp");
+		if let Some(dir) = self.path.parent() {
+			runtime::create_dir_all(dir).await.map_err(|e| store_error(dir, e))?;
+		}
+		runtime::write(&tmp, bytes).await.map_err(|e| store_error(&tmp, e))?;
+		runtime::rename(&tmp, &self.path).await.map_err(|e| store_error(&self.path, e))
+	}
+}
+
+fn store_error(path: &Path, source: io::Error) -> MidgardError {
+	MidgardError::HistoryStore { path: path.display().to_string(), source }
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::TimeZone;
+
+	use super::*;
+	use crate::TVLHistory;
+
+	fn page(starts: &[i64]) -> TVLHistory {
+		let day = |start: i64| format!(r#"{{"startTime":"{start}","endTime":"{}","poolsDepth":[],"runePriceUSD":"1.5","totalValuePooled":"100"}}"#, start + 86_400);
+		let json = format!(r#"{{"intervals":[{}],"meta":{{"startTime":"{}","endTime":"{}","poolsDepth":[],"runePriceUSD":"1.5","totalValuePooled":"100"}}}}"#, starts.iter().map(|start| day(*start)).collect::<Vec<_>>().join(","), starts[0], starts[starts.len() - 1] + 86_400);
+		serde_json::from_str(&json).unwrap()
+	}
+
+	fn at(timestamp: i64) -> DateTime<Utc> {
+		Utc.timestamp_opt(timestamp, 0).unwrap()
+	}
+
+	#[tokio::test]
+	async fn test_stores_closed_intervals() {
+		let dir = std::env::temp_dir().join(format!("midgard-rs-history-store-{}", std::process::id()));
+		let store = HistoryStore::new(&dir);
+		// the third day is still settling
+		let now = at(3 * 86_400 + 1_000);
+
+		let mut series = store.open("tvl", None, &Interval::Day).await.unwrap();
+		series.insert(&page(&[0, 86_400, 2 * 86_400]), now).await.unwrap();
+
+		let series = store.open("tvl", None, &Interval::Day).await.unwrap();
+		let stored: TVLHistory = series.interval_at(at(0), now).unwrap();
+		assert_eq!(stored.get_intervals().get_intervals().len(), 1);
+		assert_eq!(stored.get_meta().get_end_time(), &at(86_400));
+		let stored: TVLHistory = series.interval_at(at(100_000), now).unwrap();
+		assert_eq!(stored.get_meta().get_start_time(), &at(86_400));
+		assert!(series.interval_at::<TVLHistory>(at(0), at(80_000)).is_none());
+		assert!(series.interval_at::<TVLHistory>(at(2 * 86_400), now).is_none());
+		assert_eq!(series.next_start(at(0)), Some(at(86_400)));
+		assert_eq!(series.next_start(at(86_400)), None);
+
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+}
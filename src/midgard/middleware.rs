@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use crate::{EndpointGroup, Histogram, MidgardError};
+
+/// Hooks around every HTTP request sent to Midgard.
+///
+/// Middleware is added with `Midgard::add_middleware` and runs for every attempt, including retries and failover to another base url.
+/// Responses served from the response cache or the history store don't reach Midgard and don't run middleware.
+/// `before_request` hooks run in the order the middleware was added, `after_response` hooks in reverse order.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+///
+/// use midgard_rs::{Middleware, Midgard, RequestInfo, ResponseInfo};
+///
+/// /// Sends requests through a caching proxy.
+/// #[derive(Debug)]
+/// struct Proxy;
+///
+/// impl Middleware for Proxy {
+///     fn before_request(&self, request: &mut RequestInfo) {
+///         let url = request.get_url().replace("https://midgard.ninerealms.com/", "http://localhost:8080/");
+///         request.set_url(url);
+///     }
+///
+///     fn after_response(&self, request: &RequestInfo, response: &ResponseInfo<'_>) {
+///         println!("{} {} -> {:?} in {:?}", request.get_method(), request.get_url(), response.get_status(), response.get_latency());
+///     }
+/// }
+///
+/// let mut midgard = Midgard::new();
+/// midgard.add_middleware(Arc::new(Proxy));
+/// ```
+pub trait Middleware: fmt::Debug + Send + Sync {
+	/// Called before a request is sent. The url can be rewritten and headers can be added.
+	fn before_request(&self, _request: &mut RequestInfo) {}
+
+	/// Called once a response has been read, or the attempt failed.
+	fn after_response(&self, _request: &RequestInfo, _response: &ResponseInfo<'_>) {}
+}
+
+/// A request about to be sent, as seen by `Middleware`.
+#[derive(Debug, Clone)]
+pub struct RequestInfo {
+	method: String,
+	endpoint: String,
+	url: String,
+	headers: Vec<(String, String)>,
+}
+
+impl RequestInfo {
+	pub(crate) fn new(method: &str, endpoint: &str, url: String) -> Self {
+		Self { method: method.to_string(), endpoint: endpoint.to_string(), url, headers: vec![] }
+	}
+
+	#[must_use]
+	pub fn get_method(&self) -> &str {
+		&self.method
+	}
+
+	/// Returns the endpoint path, including the query string, relative to the base url.
+	#[must_use]
+	pub fn get_endpoint(&self) -> &str {
+		&self.endpoint
+	}
+
+	#[must_use]
+	pub fn get_url(&self) -> &str {
+		&self.url
+	}
+
+	/// Returns the headers added by middleware. The configured default and authentication headers are not included.
+	#[must_use]
+	pub fn get_headers(&self) -> &[(String, String)] {
+		&self.headers
+	}
+
+	pub fn set_url(&mut self, url: String) {
+		self.url = url;
+	}
+
+	/// Adds a header to the request, replacing a configured header of the same name.
+	pub fn add_header(&mut self, name: impl Into<String>, value: impl Into<String>) {
+		self.headers.push((name.into(), value.into()));
+	}
+}
+
+/// The outcome of a request, as seen by `Middleware`.
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseInfo<'a> {
+	status: Option<u16>,
+	latency: Duration,
+	body_size: usize,
+	error: Option<&'a MidgardError>,
+}
+
+impl<'a> ResponseInfo<'a> {
+	pub(crate) const fn new(status: Option<u16>, latency: Duration, body_size: usize, error: Option<&'a MidgardError>) -> Self {
+		Self { status, latency, body_size, error }
+	}
+
+	/// Returns the HTTP status, or `None` if no response was received.
+	#[must_use]
+	pub const fn get_status(&self) -> Option<u16> {
+		self.status
+	}
+
+	/// Returns the time from sending the request until the body was read or the attempt failed.
+	#[must_use]
+	pub const fn get_latency(&self) -> Duration {
+		self.latency
+	}
+
+	/// Returns the size of the response body in bytes.
+	#[must_use]
+	pub const fn get_body_size(&self) -> usize {
+		self.body_size
+	}
+
+	/// Returns the error the attempt failed with. Non-2xx responses are errors.
+	#[must_use]
+	pub const fn get_error(&self) -> Option<&'a MidgardError> {
+		self.error
+	}
+
+	#[must_use]
+	pub const fn is_success(&self) -> bool {
+		self.error.is_none()
+	}
+}
+
+/// Logs every request through the `log` crate: successful ones at `debug` level, failed ones at `warn` level.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoggingMiddleware;
+
+impl Middleware for LoggingMiddleware {
+	fn after_response(&self, request: &RequestInfo, response: &ResponseInfo<'_>) {
+		match response.get_error() {
+			None => log::debug!("{} {} -> {} ({} bytes) in {:?}", request.get_method(), request.get_url(), response.get_status().unwrap_or_default(), response.get_body_size(), response.get_latency()),
+			Some(error) => log::warn!("{} {} failed in {:?}: {error}", request.get_method(), request.get_url(), response.get_latency()),
+		}
+	}
+}
+
+/// Latency statistics collected by `TimingMiddleware`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Timing {
+	errors: u64,
+	latency: Histogram,
+}
+
+impl Timing {
+	#[must_use]
+	pub const fn get_requests(&self) -> u64 {
+		self.latency.get_count()
+	}
+
+	#[must_use]
+	pub const fn get_errors(&self) -> u64 {
+		self.errors
+	}
+
+	#[must_use]
+	pub const fn get_total_latency(&self) -> Duration {
+		self.latency.get_sum()
+	}
+
+	#[must_use]
+	pub const fn get_max_latency(&self) -> Duration {
+		self.latency.get_max()
+	}
+
+	/// Returns the mean latency, or zero before the first request.
+	#[must_use]
+	pub fn get_mean_latency(&self) -> Duration {
+		self.latency.get_mean()
+	}
+
+	/// Returns the latency histogram, with the same buckets as `EndpointMetrics::get_latency`.
+	#[must_use]
+	pub const fn get_latency(&self) -> &Histogram {
+		&self.latency
+	}
+
+	fn record(&mut self, response: &ResponseInfo<'_>) {
+		self.errors += u64::from(!response.is_success());
+		self.latency.record(response.get_latency());
+	}
+
+	fn merge(&mut self, other: &Self) {
+		self.errors += other.errors;
+		self.latency.merge(&other.latency);
+	}
+}
+
+/// Collects request counts and latencies, overall and per endpoint group.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+///
+/// use midgard_rs::{EndpointGroup, Midgard, TimingMiddleware};
+///
+/// let timing = Arc::new(TimingMiddleware::new());
+/// let mut midgard = Midgard::new();
+/// midgard.add_middleware(timing.clone());
+///
+/// // ... make some calls
+///
+/// println!("pools: {:?}", timing.get_group_timing(EndpointGroup::Pools).get_mean_latency());
+/// ```
+#[derive(Debug, Default)]
+pub struct TimingMiddleware {
+	timings: Mutex<HashMap<Option<EndpointGroup>, Timing>>,
+}
+
+impl TimingMiddleware {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Returns the statistics over all requests.
+	#[must_use]
+	pub fn get_timing(&self) -> Timing {
+		let timings = self.timings.lock().unwrap_or_else(PoisonError::into_inner);
+		timings.values().fold(Timing::default(), |mut total, timing| {
+			total.merge(timing);
+			total
+		})
+	}
+
+	/// Returns the statistics of the requests to an endpoint group.
+	#[must_use]
+	pub fn get_group_timing(&self, group: EndpointGroup) -> Timing {
+		self.timings.lock().unwrap_or_else(PoisonError::into_inner).get(&Some(group)).cloned().unwrap_or_default()
+	}
+
+	pub fn reset(&self) {
+		self.timings.lock().unwrap_or_else(PoisonError::into_inner).clear();
+	}
+}
+
+impl Middleware for TimingMiddleware {
+	fn after_response(&self, request: &RequestInfo, response: &ResponseInfo<'_>) {
+		self.timings.lock().unwrap_or_else(PoisonError::into_inner).entry(EndpointGroup::of(request.get_endpoint())).or_default().record(response);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_timing_middleware() {
+		let timing = TimingMiddleware::new();
+		let pools = RequestInfo::new("GET", "pools", "https://a/v2/pools".to_string());
+		let health = RequestInfo::new("GET", "health", "https://a/v2/health".to_string());
+		let error = MidgardError::Timeout { endpoint: "health".to_string() };
+
+		timing.after_response(&pools, &ResponseInfo::new(Some(200), Duration::from_millis(100), 10, None));
+		timing.after_response(&pools, &ResponseInfo::new(Some(200), Duration::from_millis(300), 10, None));
+		timing.after_response(&health, &ResponseInfo::new(None, Duration::from_millis(500), 0, Some(&error)));
+
+		let pools = timing.get_group_timing(EndpointGroup::Pools);
+		assert_eq!(pools.get_requests(), 2);
+		assert_eq!(pools.get_mean_latency(), Duration::from_millis(200));
+
+		let all = timing.get_timing();
+		assert_eq!(all.get_requests(), 3);
+		assert_eq!(all.get_errors(), 1);
+		assert_eq!(all.get_max_latency(), Duration::from_millis(500));
+		assert_eq!(all.get_latency().get_buckets()[6], (Some(Duration::from_millis(500)), 3));
+	}
+}
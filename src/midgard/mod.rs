@@ -1,26 +1,112 @@
-use chrono::Duration;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub use auth::*;
+pub use cache::CachePolicy;
+use cache::{is_closed_history, ResponseCache};
+use cassette::Tapes;
+pub use cassette::{Cassette, CassetteMode};
 use chrono::{DateTime, Utc};
 pub use config::*;
+pub use failover::*;
+pub use middleware::*;
+pub use rate_limit::{EndpointGroup, RateLimit};
+use rate_limit::{BucketKey, RateLimiter};
+pub use request_metrics::{EndpointMetrics, Histogram, MetricsSnapshot, HISTOGRAM_BUCKETS_MS};
+use request_metrics::RequestMetrics;
+pub use retry::*;
+use router::Router;
+pub use timeouts::*;
+pub use transport::*;
+use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
+use crate::runtime::Instant;
+
+mod auth;
+mod cache;
+mod cassette;
 mod config;
 mod endpoints;
+mod failover;
+mod history_store;
+mod middleware;
+mod paginator;
+mod rate_limit;
+mod request_metrics;
+mod retry;
+mod router;
+mod timeouts;
+mod transport;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+/// Client for the Midgard API.
+///
+/// All endpoints take `&self`, so one instance can be shared between tasks (e.g. in an `Arc`) or cloned.
+/// Clones share the HTTP connection pool, the rate limit budget and the response cache: two clones together never call the API more often than one instance would.
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Midgard {
 	config: Configuration,
-	last_call: DateTime<Utc>,
+	#[serde(skip)]
+	rate_limiter: RateLimiter,
+	#[serde(skip)]
+	router: Router,
+	#[serde(skip)]
+	cache: ResponseCache,
+	/// Set on handles returned by `bypass_cache`.
+	#[serde(skip)]
+	bypass_cache: bool,
+	#[serde(skip)]
+	metrics: RequestMetrics,
+	#[serde(skip)]
+	middleware: Vec<Arc<dyn Middleware>>,
+	#[serde(skip)]
+	tapes: Tapes,
+	#[serde(skip, default = "default_transport")]
+	transport: Arc<dyn Transport>,
 }
 
 impl Midgard {
         #[must_use]
 	pub fn new() -> Self {
-		Self { config: Configuration::default(), last_call: Utc::now() }
+		Self::with_config(Configuration::default())
 	}
 
         #[must_use]
 	pub fn with_config(config: Configuration) -> Self {
-		Self { config, last_call: Utc::now() }
+		#[cfg(not(target_arch = "wasm32"))]
+		let client = {
+			let mut builder = Client::builder();
+			if let Some(connect_timeout) = config.get_timeouts().connect() {
+				builder = builder.connect_timeout(connect_timeout);
+			}
+			builder.build().unwrap_or_default()
+		};
+		// the browser opens the connections, only the read and total timeouts apply
+		#[cfg(target_arch = "wasm32")]
+		let client = Client::new();
+		Self::with_client(config, client)
+	}
+
+	/// Creates a new instance sending its requests through `transport` instead of `reqwest`. See `Transport`.
+	#[must_use]
+	pub fn with_transport(config: Configuration, transport: Arc<dyn Transport>) -> Self {
+		Self { config, rate_limiter: RateLimiter::new(), router: Router::default(), cache: ResponseCache::default(), bypass_cache: false, metrics: RequestMetrics::default(), middleware: vec![], tapes: Tapes::default(), transport }
+	}
+
+	/// Creates a new instance using a caller supplied `reqwest::Client`.
+	/// The client is shared by every endpoint, so proxy settings, TLS roots, default headers and timeouts configured on it apply to all requests.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use midgard_rs::{Configuration, Midgard};
+	///
+	/// let client = reqwest::Client::builder().user_agent("my-app/1.0").build().unwrap();
+	/// let midgard = Midgard::with_client(Configuration::default(), client);
+	/// ```
+        #[must_use]
+	pub fn with_client(config: Configuration, client: Client) -> Self {
+		Self::with_transport(config, Arc::new(ReqwestTransport::new(client)))
 	}
 
         #[must_use]
@@ -32,46 +118,245 @@ impl Midgard {
 		self.config = config;
 	}
 
-        #[must_use]
-	pub const fn get_last_call(&self) -> DateTime<Utc> {
-		self.last_call
+	#[must_use]
+	pub fn get_transport(&self) -> &Arc<dyn Transport> {
+		&self.transport
 	}
 
-	fn set_last_call(&mut self, last_call: DateTime<Utc>) {
-		self.last_call = last_call;
+	pub fn set_transport(&mut self, transport: Arc<dyn Transport>) {
+		self.transport = transport;
 	}
 
-	/// Returns a future timestamp of when it is ok to call the Midgard API again
-	fn ok_to_call_at(&self) -> DateTime<Utc> {
-		let rate_limit: i64 = i64::try_from(self.config.get_rate_limit_ms()).map_or(1000, |rate_limit| rate_limit);
-		let rate_limit = Duration::try_milliseconds(rate_limit).map_or_else(Duration::zero, |rate_limit| rate_limit);
-		self.last_call.checked_add_signed(rate_limit).map_or_else(Utc::now, |res| res)
+	/// Sends the requests of this instance with `client` through a `ReqwestTransport`.
+	pub fn set_client(&mut self, client: Client) {
+		self.transport = Arc::new(ReqwestTransport::new(client));
 	}
 
-	/// Sleeps until it is ok to call the Midgard API again
-	async fn sleep_until_ok_to_call(&mut self) {
-		let now = Utc::now();
-		let ok_to_call_at = self.ok_to_call_at();
-		if now < ok_to_call_at {
-			let sleep_duration = ok_to_call_at - now;
-			tokio::time::sleep(sleep_duration.to_std().unwrap()).await;
+	#[must_use]
+	pub fn get_middleware(&self) -> &[Arc<dyn Middleware>] {
+		&self.middleware
+	}
+
+	/// Adds a middleware that runs around every request sent by this instance and the clones made afterwards.
+	pub fn add_middleware(&mut self, middleware: Arc<dyn Middleware>) {
+		self.middleware.push(middleware);
+	}
+
+	pub fn clear_middleware(&mut self) {
+		self.middleware.clear();
+	}
+
+	/// Returns the time of the last call slot handed out by the rate limiter. While calls are waiting for the rate limiter this lies in the future.
+	#[must_use]
+	pub fn get_last_call(&self) -> DateTime<Utc> {
+		self.rate_limiter.last_call()
+	}
+
+	/// Returns the request counts, errors, retries, cache hits and latency histograms collected so far by this instance and its clones,
+	/// per endpoint path and summable per endpoint group with `MetricsSnapshot::get_group`.
+	#[must_use]
+	pub fn get_metrics(&self) -> MetricsSnapshot {
+		self.metrics.snapshot()
+	}
+
+	/// Resets all collected metrics to zero.
+	pub fn reset_metrics(&self) {
+		self.metrics.reset();
+	}
+
+	/// Returns a handle that doesn't read from the response cache. Fresh responses still replace cached ones, so the handle can be used to refresh entries.
+	/// It shares everything else, including the cache, with `self`.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use midgard_rs::{CachePolicy, Configuration, Midgard};
+	/// # tokio_test::block_on(async {
+	/// let mut config = Configuration::default();
+	/// config.set_cache_policy(CachePolicy::new(true));
+	/// let midgard = Midgard::with_config(config);
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
+	///
+	/// let cached = midgard.get_network_data().await.unwrap();
+	/// let fresh = midgard.bypass_cache().get_network_data().await.unwrap();
+	/// # });
+	/// ```
+	#[must_use]
+	pub fn bypass_cache(&self) -> Self {
+		Self { bypass_cache: true, ..self.clone() }
+	}
+
+	/// Returns a handle that uses `timeouts` instead of the configured ones and shares everything else with `self`.
+	/// The connect timeout is fixed when the HTTP client is built and can't be overridden this way.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use midgard_rs::{HistoryQuery, Interval, Midgard, Timeouts};
+	/// # tokio_test::block_on(async {
+	/// let midgard = Midgard::new();
+	/// # let server = midgard_rs::test_support::MockMidgard::start().await;
+	/// # let midgard = server.get_midgard();
+	/// let query = HistoryQuery::builder().interval(Interval::Day).count(400).build().unwrap();
+	/// let history = midgard.with_timeouts(Timeouts::new(None, Some(60_000), Some(120_000))).get_depth_and_price_history("BTC.BTC", &query).await.unwrap();
+	/// # });
+	/// ```
+	#[must_use]
+	pub fn with_timeouts(&self, timeouts: Timeouts) -> Self {
+		let mut midgard = self.clone();
+		midgard.config.set_timeouts(timeouts);
+		midgard
+	}
+
+	/// Removes all cached responses.
+	pub fn clear_cache(&self) {
+		self.cache.clear();
+	}
+
+	/// Removes the cached responses of an endpoint group.
+	pub fn invalidate_cache(&self, group: EndpointGroup) {
+		self.cache.invalidate_group(group);
+	}
+
+	/// Returns the cached response for `endpoint`, if caching is enabled and a fresh one is available
+	pub(crate) fn cached_response(&self, endpoint: &str) -> Option<(u16, String)> {
+		if !self.config.get_cache_policy().get_enabled() || self.bypass_cache {
+			return None;
+		}
+		self.cache.get(&self.cache_key(endpoint), Instant::now())
+	}
+
+	/// Caches a successful response for `endpoint` according to the cache policy
+	pub(crate) fn cache_response(&self, endpoint: &str, status: u16, body: &str) {
+		let policy = self.config.get_cache_policy();
+		if !policy.get_enabled() {
+			return;
 		}
-		self.set_last_call(Utc::now());
+		let group = EndpointGroup::of(endpoint);
+		let now = Instant::now();
+		let expires_at = if group == Some(EndpointGroup::History) && policy.get_cache_closed_history() && is_closed_history(body, Utc::now()) {
+			None
+		} else {
+			match group.and_then(|group| policy.get_ttl_ms(group)) {
+				Some(ttl_ms) => now.checked_add(Duration::from_millis(ttl_ms)),
+				None => return,
+			}
+		};
+		self.cache.insert(self.cache_key(endpoint), group, (status, body.to_string()), expires_at, policy.get_max_entries(), now);
+	}
+
+	/// Sleeps until the global rate limit and the limit of the endpoint's group allow another call
+	pub(crate) async fn wait_for_rate_limit(&self, endpoint: &str) {
+		self.rate_limiter.wait(&self.rate_limits(endpoint)).await;
+	}
+
+	/// Lowers the rate limits that apply to `endpoint` after Midgard answered with 429
+	pub(crate) fn rate_limited(&self, endpoint: &str, retry_after: Option<Duration>) {
+		if self.config.get_adaptive_rate_limit() {
+			self.rate_limiter.throttle(&self.rate_limits(endpoint), retry_after);
+		}
+	}
+
+	/// Lets lowered rate limits recover after a successful call
+	pub(crate) fn rate_limit_ok(&self, endpoint: &str) {
+		if self.config.get_adaptive_rate_limit() {
+			self.rate_limiter.recover(&self.rate_limits(endpoint));
+		}
+	}
+
+	/// Returns the base urls to try for a request in the order given by the routing mode, and starts probing them in the background when there is more than one or reads are routed by height.
+	/// When no instance qualifies for height based routing all of them are probed right away; if none qualifies after that the list is empty.
+	pub(crate) async fn base_urls_for_request(&self) -> Vec<String> {
+		let base_urls = self.config.get_base_urls();
+		let routing = self.config.get_failover_policy().get_routing();
+		if base_urls.len() > 1 || routing != Routing::Ordered {
+			self.router.start_probing(&self.transport, self.config.clone());
+		}
+
+		let candidates = self.router.candidates(&base_urls, routing);
+		if !candidates.is_empty() {
+			return candidates;
+		}
+		self.router.probe_all(&self.transport, &self.config).await;
+		self.router.candidates(&base_urls, routing)
+	}
+
+	pub(crate) const fn get_router(&self) -> &Router {
+		&self.router
+	}
+
+	pub(crate) const fn get_request_metrics(&self) -> &RequestMetrics {
+		&self.metrics
+	}
+
+	pub(crate) const fn get_tapes(&self) -> &Tapes {
+		&self.tapes
+	}
+
+	/// Keys cached responses by the primary base url and `endpoint`, not by the url actually requested, which is only known once a request has been sent.
+	fn cache_key(&self, endpoint: &str) -> String {
+		self.config.get_base_url().to_string() + endpoint
+	}
+
+	fn rate_limits(&self, endpoint: &str) -> Vec<(BucketKey, RateLimit)> {
+		let mut limits = vec![(None, self.config.get_rate_limit())];
+		if let Some(group) = EndpointGroup::of(endpoint) {
+			if let Some(limit) = self.config.get_group_rate_limit(group) {
+				limits.push((Some(group), limit));
+			}
+		}
+		limits
+	}
+}
+
+impl Default for Midgard {
+	fn default() -> Self {
+		Self::new()
 	}
 }
 
+fn default_transport() -> Arc<dyn Transport> {
+	Arc::new(ReqwestTransport::default())
+}
 
 #[cfg(test)]
 mod tests {
         use rand::prelude::*;
 	use serde_json::json;
         use crate::GetActionList;
+	use crate::test_support::MockMidgard;
 
 	use super::*;
 
+	#[test]
+	fn test_midgard_is_clone_send_and_sync() {
+		fn assert_clone_send_sync<T: Clone + Send + Sync>() {}
+		assert_clone_send_sync::<Midgard>();
+	}
+
+	#[tokio::test]
+	async fn test_clones_share_rate_limit() {
+		let midgard = Midgard::with_config(Configuration::new(String::new(), 50));
+		let clone = midgard.clone();
+		let started_at = Utc::now();
+		let task = tokio::spawn({
+			let midgard = midgard.clone();
+			async move { midgard.wait_for_rate_limit("health").await }
+		});
+
+		tokio::join!(midgard.wait_for_rate_limit("health"), clone.wait_for_rate_limit("health"));
+		task.await.unwrap();
+
+		// three calls through three handles used three consecutive slots of the same budget
+		assert!(clone.get_last_call() - started_at >= chrono::TimeDelta::milliseconds(100));
+		assert!(Utc::now() >= clone.get_last_call());
+	}
+
         #[tokio::test]
         async fn endpoints() {
-                let mut midgard = Midgard::new();
+                let server = MockMidgard::start().await;
+                let midgard = server.get_midgard();
 
 
                 // actions
@@ -0,0 +1,233 @@
+use std::ops::Range;
+
+use chrono::{DateTime, Months, TimeDelta, Utc};
+
+use crate::{DepthHistory, DepthHistoryIntervals, DepthHistoryMeta, EarningsHistory, EarningsIntervals, HistoryQuery, Interval, LiquidityChangeHistory, LiquidityChangeIntervals, MidgardError, SaversHistory, SaversHistoryIntervals, SaversHistoryMeta, SwapHistory, SwapIntervals, TVLHistory, TVLIntervals};
+
+/// History responses that can be fetched page by page and merged back together.
+pub trait PagedHistory: Default {
+	/// Merges a later, adjacent page into this one. Intervals are appended, skipping any that start before the last one held ends, and the meta is recomputed over the combined range.
+	fn merge(&mut self, next: Self);
+	/// Splits the page into one page per interval, each with the meta Midgard returns for that interval alone. The inverse of `merge`.
+	fn split(self) -> Vec<Self>;
+	fn start_time(&self) -> DateTime<Utc>;
+	fn end_time(&self) -> DateTime<Utc>;
+	fn is_empty(&self) -> bool;
+}
+
+/// Implements `PagedHistory` for history responses, given their intervals type and a function returning the meta of each interval from the meta of the page.
+macro_rules! impl_paged_history {
+	($($history:ident($intervals:ident, $split_meta:expr)),* $(,)?) => {
+		$(
+			impl PagedHistory for $history {
+				fn merge(&mut self, next: Self) {
+					let last_end_time = self.intervals.0.last().map_or(DateTime::<Utc>::MIN_UTC, |interval| *interval.get_end_time());
+					self.intervals.0.extend(next.intervals.0.into_iter().filter(|interval| *interval.get_start_time() >= last_end_time));
+					self.meta.merge(next.meta);
+				}
+
+				fn split(self) -> Vec<Self> {
+					let metas: Vec<_> = $split_meta(&self.meta, &self.intervals.0);
+					self.intervals.0.into_iter().zip(metas).map(|(interval, meta)| Self { intervals: $intervals(vec![interval]), meta }).collect()
+				}
+
+				fn start_time(&self) -> DateTime<Utc> {
+					*self.get_meta().get_start_time()
+				}
+
+				fn end_time(&self) -> DateTime<Utc> {
+					*self.get_meta().get_end_time()
+				}
+
+				fn is_empty(&self) -> bool {
+					self.get_intervals().is_empty()
+				}
+			}
+		)*
+	};
+}
+
+impl_paged_history!(
+	DepthHistory(DepthHistoryIntervals, DepthHistoryMeta::split),
+	EarningsHistory(EarningsIntervals, meta_per_interval),
+	LiquidityChangeHistory(LiquidityChangeIntervals, meta_per_interval),
+	SaversHistory(SaversHistoryIntervals, SaversHistoryMeta::split),
+	SwapHistory(SwapIntervals, meta_per_interval),
+	TVLHistory(TVLIntervals, meta_per_interval),
+);
+
+/// Returns the meta of each interval for histories whose meta follows from a single interval alone.
+fn meta_per_interval<M, I>(_meta: &M, intervals: &[I]) -> Vec<M>
+where
+	for<'a> M: From<&'a I>,
+{
+	intervals.iter().map(M::from).collect()
+}
+
+/// Most intervals Midgard returns for a single request.
+pub const MAX_PAGE_INTERVALS: u32 = 400;
+
+/// Splits a history range into queries Midgard can answer and merges the returned pages.
+///
+/// Midgard returns at most 400 intervals per request. Each page is requested with a `from..to` spanning at most `MAX_PAGE_INTERVALS - 1` intervals,
+/// which holds at most `MAX_PAGE_INTERVALS` once Midgard rounds `from` down to an interval boundary. The next page starts at the `meta.endTime` of the previous one until the range is covered.
+pub struct HistoryPaginator<H> {
+	interval: Interval,
+	from: DateTime<Utc>,
+	to: DateTime<Utc>,
+	history: Option<H>,
+	done: bool,
+}
+
+impl<H: PagedHistory> HistoryPaginator<H> {
+	pub const fn new(interval: Interval, range: Range<DateTime<Utc>>) -> Self {
+		Self { interval, from: range.start, to: range.end, history: None, done: false }
+	}
+
+	/// Returns the query for the next page, or `None` once the range is covered.
+	pub fn next_query(&self) -> Result<Option<HistoryQuery>, MidgardError> {
+		self.next_query_before(None)
+	}
+
+	/// Like `next_query`, but the page ends with the interval before `before` if that is earlier than the end of the range.
+	/// Used to fetch only the gap up to a page that is already stored.
+	pub fn next_query_before(&self, before: Option<DateTime<Utc>>) -> Result<Option<HistoryQuery>, MidgardError> {
+		let Some(from) = self.next_from() else {
+			return Ok(None);
+		};
+		let to = before.and_then(|before| before.checked_sub_signed(TimeDelta::seconds(1))).filter(|before| *before > from).map_or(self.to, |before| before.min(self.to));
+		let to = page_end(&self.interval, from).map_or(to, |page_end| page_end.min(to));
+		HistoryQuery::builder().interval(self.interval.clone()).range(from..to).build().map(Some)
+	}
+
+	/// Returns the start of the next page, or `None` once the range is covered.
+	pub fn next_from(&self) -> Option<DateTime<Utc>> {
+		if self.done || self.from >= self.to {
+			return None;
+		}
+		Some(self.from)
+	}
+
+	pub const fn get_to(&self) -> DateTime<Utc> {
+		self.to
+	}
+
+	/// Adds a fetched page. Paging stops on an empty page or when Midgard makes no progress.
+	pub fn push(&mut self, page: H) {
+		let end_time = page.end_time();
+		if page.is_empty() || end_time <= self.from {
+			self.done = true;
+		}
+		if page.is_empty() {
+			return;
+		}
+		self.from = end_time;
+		match &mut self.history {
+			Some(history) => history.merge(page),
+			None => self.history = Some(page),
+		}
+	}
+
+	pub fn finish(self) -> H {
+		self.history.unwrap_or_default()
+	}
+}
+
+/// Returns the end of a page starting at `from`, `MAX_PAGE_INTERVALS - 1` intervals later. `None` if that is past the representable dates.
+fn page_end(interval: &Interval, from: DateTime<Utc>) -> Option<DateTime<Utc>> {
+	let intervals = MAX_PAGE_INTERVALS - 1;
+	match interval {
+		Interval::FiveMinutes => from.checked_add_signed(TimeDelta::minutes(5 * i64::from(intervals))),
+		Interval::Hour => from.checked_add_signed(TimeDelta::hours(i64::from(intervals))),
+		Interval::Day => from.checked_add_signed(TimeDelta::days(i64::from(intervals))),
+		Interval::Week => from.checked_add_signed(TimeDelta::weeks(i64::from(intervals))),
+		Interval::Month => from.checked_add_months(Months::new(intervals)),
+		Interval::Quarter => from.checked_add_months(Months::new(3 * intervals)),
+		Interval::Year => from.checked_add_months(Months::new(12 * intervals)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::TimeZone;
+
+	use super::*;
+
+	fn page(start: i64, end: i64, intervals: usize) -> TVLHistory {
+		let step = (end - start) / i64::try_from(intervals.max(1)).unwrap();
+		let intervals = (0..intervals)
+			.map(|i| {
+				let i = i64::try_from(i).unwrap();
+				format!(r#"{{"startTime":"{}","endTime":"{}","poolsDepth":[],"runePriceUSD":"1.0","totalValuePooled":"0"}}"#, start + i * step, start + (i + 1) * step)
+			})
+			.collect::<Vec<_>>()
+			.join(",");
+		let json = format!(r#"{{"intervals":[{intervals}],"meta":{{"startTime":"{start}","endTime":"{end}","poolsDepth":[],"runePriceUSD":"1.0","totalValuePooled":"0"}}}}"#);
+		serde_json::from_str(&json).unwrap()
+	}
+
+	#[test]
+	fn test_paginator_covers_range() {
+		let from = Utc.timestamp_opt(0, 0).unwrap();
+		let to = Utc.timestamp_opt(1_000, 0).unwrap();
+		let mut paginator = HistoryPaginator::<TVLHistory>::new(Interval::Day, from..to);
+
+		let query = paginator.next_query().unwrap().unwrap();
+		assert_eq!(query.get_from(), Some(&from));
+		assert_eq!(query.get_count(), None);
+		paginator.push(page(0, 400, 4));
+
+		let query = paginator.next_query().unwrap().unwrap();
+		assert_eq!(query.get_from(), Some(&Utc.timestamp_opt(400, 0).unwrap()));
+		paginator.push(page(300, 1_000, 7));
+
+		assert!(paginator.next_query().unwrap().is_none());
+
+		let history = paginator.finish();
+		assert_eq!(history.get_intervals().get_intervals().len(), 10);
+		assert_eq!(history.get_meta().get_start_time(), &from);
+		assert_eq!(history.get_meta().get_end_time(), &to);
+	}
+
+	#[test]
+	fn test_paginator_stops_on_empty_page() {
+		let from = Utc.timestamp_opt(0, 0).unwrap();
+		let to = Utc.timestamp_opt(1_000, 0).unwrap();
+		let mut paginator = HistoryPaginator::<TVLHistory>::new(Interval::Day, from..to);
+
+		paginator.push(page(0, 400, 4));
+		paginator.push(page(400, 400, 0));
+
+		assert!(paginator.next_query().unwrap().is_none());
+		assert_eq!(paginator.finish().get_intervals().get_intervals().len(), 4);
+	}
+
+	#[test]
+	fn test_next_query_before_stored_page() {
+		let from = Utc.timestamp_opt(0, 0).unwrap();
+		let to = Utc.timestamp_opt(1_000, 0).unwrap();
+		let paginator = HistoryPaginator::<TVLHistory>::new(Interval::Day, from..to);
+
+		let query = paginator.next_query_before(Some(Utc.timestamp_opt(400, 0).unwrap())).unwrap().unwrap();
+		assert_eq!(query.get_to(), Some(&Utc.timestamp_opt(399, 0).unwrap()));
+
+		let query = paginator.next_query_before(Some(Utc.timestamp_opt(2_000, 0).unwrap())).unwrap().unwrap();
+		assert_eq!(query.get_to(), Some(&to));
+	}
+
+	#[test]
+	fn test_pages_are_bounded() {
+		let from = Utc.timestamp_opt(0, 0).unwrap();
+		let to = Utc.timestamp_opt(100_000_000, 0).unwrap();
+
+		let query = HistoryPaginator::<TVLHistory>::new(Interval::Day, from..to).next_query().unwrap().unwrap();
+		assert_eq!(query.get_to(), Some(&(from + TimeDelta::days(399))));
+
+		let query = HistoryPaginator::<TVLHistory>::new(Interval::FiveMinutes, from..to).next_query().unwrap().unwrap();
+		assert_eq!(query.get_to(), Some(&(from + TimeDelta::minutes(5 * 399))));
+
+		// a range shorter than a page is requested as is
+		let query = HistoryPaginator::<TVLHistory>::new(Interval::Month, from..to).next_query().unwrap().unwrap();
+		assert_eq!(query.get_to(), Some(&to));
+	}
+}
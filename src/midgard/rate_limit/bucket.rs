@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::runtime::{self, Instant};
+use crate::{EndpointGroup, RateLimit};
+
+/// Upper bound of the factor a limit's interval is multiplied by after repeated 429 responses.
+const MAX_SLOWDOWN: u32 = 16;
+
+/// Key of a token bucket: `None` is the global limit, `Some(group)` the limit of an endpoint group.
+pub type BucketKey = Option<EndpointGroup>;
+
+/// Rate limit state shared by a `Midgard` instance and all of its clones.
+///
+/// Every limit is a token bucket. A caller reserves a slot in each bucket that applies to its endpoint while holding the lock and then sleeps without it,
+/// so concurrent tasks are spaced out by the limits instead of being serialized behind one another.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+	state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+struct State {
+	last_call: DateTime<Utc>,
+	buckets: HashMap<BucketKey, Bucket>,
+}
+
+/// A token bucket tracked by the time at which it will be full again (the generic cell rate algorithm).
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+	full_at: Instant,
+	/// Factor the configured interval is multiplied by, raised on 429 responses.
+	slowdown: u32,
+}
+
+impl Bucket {
+	const fn new(now: Instant) -> Self {
+		Self { full_at: now, slowdown: 1 }
+	}
+
+	const fn interval(&self, limit: RateLimit) -> Duration {
+		Duration::from_millis(limit.get_interval_ms()).saturating_mul(self.slowdown)
+	}
+
+	/// Time it takes to refill the tokens that may be used back to back.
+	const fn tolerance(&self, limit: RateLimit) -> Duration {
+		self.interval(limit).saturating_mul(limit.get_burst() - 1)
+	}
+
+	/// Returns the earliest time a token is available, not earlier than `now`.
+	fn available_at(&self, limit: RateLimit, now: Instant) -> Instant {
+		self.full_at.checked_sub(self.tolerance(limit)).map_or(now, |available_at| available_at.max(now))
+	}
+
+	fn take(&mut self, limit: RateLimit, now: Instant) {
+		self.full_at = self.full_at.max(now) + self.interval(limit);
+	}
+
+	/// Slows the bucket down and empties it so that no token is available before `wait` has passed.
+	fn throttle(&mut self, limit: RateLimit, wait: Option<Duration>, now: Instant) {
+		self.slowdown = (self.slowdown * 2).min(MAX_SLOWDOWN);
+		let wait = wait.unwrap_or_else(|| self.interval(limit));
+		self.full_at = self.full_at.max(now + wait + self.tolerance(limit));
+	}
+
+	fn release(&mut self, limit: RateLimit, now: Instant) {
+		self.full_at = self.full_at.checked_sub(self.interval(limit)).map_or(now, |full_at| full_at.max(now));
+	}
+
+	fn recover(&mut self) {
+		self.slowdown = self.slowdown.saturating_sub(1).max(1);
+	}
+}
+
+impl RateLimiter {
+	pub fn new() -> Self {
+		Self { state: Arc::new(Mutex::new(State { last_call: Utc::now(), buckets: HashMap::new() })) }
+	}
+
+	/// Returns the time of the last reserved call slot. This may lie in the future while callers are waiting.
+	pub fn last_call(&self) -> DateTime<Utc> {
+		self.lock().last_call
+	}
+
+	/// Waits until a token is available in every one of `limits`.
+	///
+	/// Cancellation safe: if the future is dropped before the reserved slot has come, the tokens are given back.
+	pub async fn wait(&self, limits: &[(BucketKey, RateLimit)]) {
+		let now = Instant::now();
+		let call_at = self.reserve(limits, now);
+		if call_at > now {
+			let reservation = Reservation { limiter: self, limits };
+			runtime::sleep(call_at - now).await;
+			std::mem::forget(reservation);
+		}
+	}
+
+	/// Lowers `limits` after a 429 response. No tokens are handed out before `retry_after`, or one interval if the server sent no hint.
+	pub fn throttle(&self, limits: &[(BucketKey, RateLimit)], retry_after: Option<Duration>) {
+		let now = Instant::now();
+		let mut state = self.lock();
+		for (key, limit) in limits {
+			state.buckets.entry(*key).or_insert_with(|| Bucket::new(now)).throttle(*limit, retry_after, now);
+		}
+	}
+
+	/// Moves `limits` back towards their configured rate after a successful request.
+	pub fn recover(&self, limits: &[(BucketKey, RateLimit)]) {
+		let mut state = self.lock();
+		for (key, _) in limits {
+			if let Some(bucket) = state.buckets.get_mut(key) {
+				bucket.recover();
+			}
+		}
+	}
+
+	/// Reserves the first time at which every bucket in `limits` has a token, taking one token from each.
+	/// A bucket that has a token earlier than the others is not held back by them, so a slow group limit doesn't stall the global budget.
+	fn reserve(&self, limits: &[(BucketKey, RateLimit)], now: Instant) -> Instant {
+		let mut state = self.lock();
+		let call_at = limits.iter().map(|(key, limit)| state.buckets.get(key).map_or(now, |bucket| bucket.available_at(*limit, now))).max().unwrap_or(now);
+		for (key, limit) in limits {
+			state.buckets.entry(*key).or_insert_with(|| Bucket::new(now)).take(*limit, now);
+		}
+		state.last_call = TimeDelta::from_std(call_at - now).ok().and_then(|wait| Utc::now().checked_add_signed(wait)).unwrap_or(DateTime::<Utc>::MAX_UTC);
+		call_at
+	}
+
+	/// Gives back the tokens of a reservation that was not used.
+	fn release(&self, limits: &[(BucketKey, RateLimit)], now: Instant) {
+		let mut state = self.lock();
+		for (key, limit) in limits {
+			if let Some(bucket) = state.buckets.get_mut(key) {
+				bucket.release(*limit, now);
+			}
+		}
+	}
+
+	fn lock(&self) -> MutexGuard<'_, State> {
+		self.state.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+}
+
+/// Tokens taken by a caller that is still waiting for its slot. Dropping it releases them.
+struct Reservation<'a> {
+	limiter: &'a RateLimiter,
+	limits: &'a [(BucketKey, RateLimit)],
+}
+
+impl Drop for Reservation<'_> {
+	fn drop(&mut self) {
+		self.limiter.release(self.limits, Instant::now());
+	}
+}
+
+impl Default for RateLimiter {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const SECOND: Duration = Duration::from_secs(1);
+
+	#[test]
+	fn test_reserve_spaces_out_calls() {
+		let limiter = RateLimiter::new();
+		let shared = limiter.clone();
+		let limits = [(None, RateLimit::new(1_000, 1))];
+		let now = Instant::now();
+
+		assert_eq!(limiter.reserve(&limits, now), now);
+		assert_eq!(limiter.reserve(&limits, now), now + SECOND);
+		assert_eq!(shared.reserve(&limits, now), now + 2 * SECOND);
+	}
+
+	#[test]
+	fn test_reserve_allows_bursts() {
+		let limiter = RateLimiter::new();
+		let limits = [(None, RateLimit::new(1_000, 3))];
+		let now = Instant::now();
+
+		assert_eq!(limiter.reserve(&limits, now), now);
+		assert_eq!(limiter.reserve(&limits, now), now);
+		assert_eq!(limiter.reserve(&limits, now), now);
+		assert_eq!(limiter.reserve(&limits, now), now + SECOND);
+
+		// after an idle period the burst is available again
+		let later = now + 10 * SECOND;
+		assert_eq!(limiter.reserve(&limits, later), later);
+		assert_eq!(limiter.reserve(&limits, later), later);
+	}
+
+	#[test]
+	fn test_reserve_applies_group_limits() {
+		let limiter = RateLimiter::new();
+		let global = (None, RateLimit::new(0, 1));
+		let history = (Some(EndpointGroup::History), RateLimit::new(1_000, 1));
+		let now = Instant::now();
+
+		assert_eq!(limiter.reserve(&[global, history], now), now);
+		assert_eq!(limiter.reserve(&[global, history], now), now + SECOND);
+		assert_eq!(limiter.reserve(&[global], now), now);
+	}
+
+	#[test]
+	fn test_throttle_and_recover() {
+		let limiter = RateLimiter::new();
+		let limits = [(None, RateLimit::new(1_000, 1))];
+		let now = Instant::now();
+
+		limiter.throttle(&limits, Some(5 * SECOND));
+		let call_at = limiter.reserve(&limits, now);
+		assert!(call_at >= now + 5 * SECOND);
+
+		// the interval is doubled until requests succeed again
+		assert_eq!(limiter.reserve(&limits, now), call_at + 2 * SECOND);
+		limiter.recover(&limits);
+		assert_eq!(limiter.reserve(&limits, now), call_at + 4 * SECOND);
+		assert_eq!(limiter.reserve(&limits, now), call_at + 5 * SECOND);
+	}
+
+	#[tokio::test]
+	async fn test_cancelled_wait_releases_its_slot() {
+		let limiter = RateLimiter::new();
+		let limits = [(None, RateLimit::new(1_000, 1))];
+		let now = Instant::now();
+		limiter.reserve(&limits, now);
+
+		// the caller gives up while waiting for the slot at now + 1s
+		assert!(tokio::time::timeout(Duration::from_millis(10), limiter.wait(&limits)).await.is_err());
+
+		let call_at = limiter.reserve(&limits, Instant::now());
+		assert!(call_at <= now + SECOND);
+	}
+}
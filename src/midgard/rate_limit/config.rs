@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+
+/// A token bucket limit: requests are allowed at a sustained rate of one per `interval_ms`, and up to `burst` requests may be sent back to back after an idle period.
+///
+/// # Example
+///
+/// ```rust
+/// use midgard_rs::{Configuration, EndpointGroup, RateLimit};
+///
+/// let mut config = Configuration::default();
+///
+/// // 10 requests per second with bursts of up to 20 requests
+/// config.set_rate_limit(RateLimit::new(100, 20));
+///
+/// // history queries are expensive, allow only one every 2 seconds
+/// config.set_group_rate_limit(EndpointGroup::History, RateLimit::new(2_000, 1));
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+	interval_ms: u64,
+	burst: u32,
+}
+
+impl RateLimit {
+	/// `burst` is clamped to at least 1.
+	#[must_use]
+	pub const fn new(interval_ms: u64, burst: u32) -> Self {
+		Self { interval_ms, burst: if burst == 0 { 1 } else { burst } }
+	}
+
+	#[must_use]
+	pub const fn get_interval_ms(&self) -> u64 {
+		self.interval_ms
+	}
+
+	#[must_use]
+	pub const fn get_burst(&self) -> u32 {
+		self.burst
+	}
+
+	pub const fn set_interval_ms(&mut self, interval_ms: u64) {
+		self.interval_ms = interval_ms;
+	}
+
+	/// `burst` is clamped to at least 1.
+	pub const fn set_burst(&mut self, burst: u32) {
+		self.burst = if burst == 0 { 1 } else { burst };
+	}
+}
+
+/// Groups of endpoints that can be given their own `RateLimit` in addition to the global one.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum EndpointGroup {
+	/// `actions`
+	Actions,
+	/// `balance/{address}`
+	Balance,
+	/// `borrowers`, `borrower/{address}`
+	Borrowers,
+	/// `health`
+	Health,
+	/// `history/*`
+	History,
+	/// `members`, `member/{address}`
+	Members,
+	/// `churns`, `network`, `nodes`, `stats`
+	Network,
+	/// `knownpools`, `pools`, `pool/{pool}`, `pool/{pool}/stats`
+	Pools,
+	/// `saver/{address}`
+	Savers,
+	/// `thorname/*`
+	Thorname,
+}
+
+impl EndpointGroup {
+	/// Returns the group of an endpoint path such as `history/depths/BTC.BTC?interval=day`, or `None` for unknown endpoints.
+	#[must_use]
+	pub fn of(endpoint: &str) -> Option<Self> {
+		match endpoint.split(['/', '?']).next().unwrap_or_default() {
+			"actions" => Some(Self::Actions),
+			"balance" => Some(Self::Balance),
+			"borrower" | "borrowers" => Some(Self::Borrowers),
+			"health" => Some(Self::Health),
+			"history" => Some(Self::History),
+			"member" | "members" => Some(Self::Members),
+			"churns" | "network" | "nodes" | "stats" => Some(Self::Network),
+			"knownpools" | "pool" | "pools" => Some(Self::Pools),
+			"saver" | "savers" => Some(Self::Savers),
+			"thorname" => Some(Self::Thorname),
+			_ => None,
+		}
+	}
+
+	/// Returns the name of the group, as used in configuration files and metric labels.
+	#[must_use]
+	pub const fn as_str(&self) -> &'static str {
+		match self {
+			Self::Actions => "actions",
+			Self::Balance => "balance",
+			Self::Borrowers => "borrowers",
+			Self::Health => "health",
+			Self::History => "history",
+			Self::Members => "members",
+			Self::Network => "network",
+			Self::Pools => "pools",
+			Self::Savers => "savers",
+			Self::Thorname => "thorname",
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_endpoint_group_of() {
+		assert_eq!(EndpointGroup::of("health"), Some(EndpointGroup::Health));
+		assert_eq!(EndpointGroup::of("history/depths/BTC.BTC?interval=day&count=10"), Some(EndpointGroup::History));
+		assert_eq!(EndpointGroup::of("actions?asset=BTC.BTC&limit=10"), Some(EndpointGroup::Actions));
+		assert_eq!(EndpointGroup::of("pool/BTC.BTC/stats"), Some(EndpointGroup::Pools));
+		assert_eq!(EndpointGroup::of("stats"), Some(EndpointGroup::Network));
+		assert_eq!(EndpointGroup::of("unknown"), None);
+		assert_eq!(serde_json::to_string(&EndpointGroup::Thorname).unwrap(), format!("\"{}\"", EndpointGroup::Thorname.as_str()));
+	}
+}
@@ -0,0 +1,7 @@
+//! Rate limits: the configured `RateLimit`s and the token buckets enforcing them.
+
+pub use bucket::{BucketKey, RateLimiter};
+pub use config::{EndpointGroup, RateLimit};
+
+mod bucket;
+mod config;
@@ -0,0 +1,304 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use crate::{EndpointGroup, MidgardError};
+
+/// Upper bounds of the histogram buckets in milliseconds. A last, unbounded bucket collects everything above.
+pub const HISTOGRAM_BUCKETS_MS: [u64; 12] = [5, 10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 30_000];
+
+/// A latency histogram with the fixed buckets of `HISTOGRAM_BUCKETS_MS`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Histogram {
+	counts: [u64; HISTOGRAM_BUCKETS_MS.len() + 1],
+	count: u64,
+	sum: Duration,
+	max: Duration,
+}
+
+impl Histogram {
+	/// Returns the number of recorded durations.
+	#[must_use]
+	pub const fn get_count(&self) -> u64 {
+		self.count
+	}
+
+	/// Returns the sum of the recorded durations.
+	#[must_use]
+	pub const fn get_sum(&self) -> Duration {
+		self.sum
+	}
+
+	/// Returns the longest recorded duration.
+	#[must_use]
+	pub const fn get_max(&self) -> Duration {
+		self.max
+	}
+
+	/// Returns the mean of the recorded durations, or zero before the first one.
+	#[must_use]
+	pub fn get_mean(&self) -> Duration {
+		u32::try_from(self.count).ok().filter(|count| *count > 0).map_or(Duration::ZERO, |count| self.sum / count)
+	}
+
+	/// Returns the cumulative count of each bucket together with its upper bound, Prometheus style. The last bucket has no upper bound and holds the total count.
+	#[must_use]
+	pub fn get_buckets(&self) -> Vec<(Option<Duration>, u64)> {
+		let bounds = HISTOGRAM_BUCKETS_MS.iter().map(|ms| Some(Duration::from_millis(*ms))).chain([None]);
+		bounds.zip(self.counts.iter().scan(0, |total, count| {
+			*total += count;
+			Some(*total)
+		})).collect()
+	}
+
+	pub(crate) fn record(&mut self, duration: Duration) {
+		let bucket = HISTOGRAM_BUCKETS_MS.iter().position(|ms| duration <= Duration::from_millis(*ms)).unwrap_or(HISTOGRAM_BUCKETS_MS.len());
+		self.counts[bucket] += 1;
+		self.count += 1;
+		self.sum += duration;
+		self.max = self.max.max(duration);
+	}
+
+	pub(crate) fn merge(&mut self, other: &Self) {
+		for (count, other) in self.counts.iter_mut().zip(other.counts) {
+			*count += other;
+		}
+		self.count += other.count;
+		self.sum += other.sum;
+		self.max = self.max.max(other.max);
+	}
+}
+
+/// Counters and histograms of the requests to one endpoint, or summed over several.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EndpointMetrics {
+	requests: u64,
+	errors: BTreeMap<&'static str, u64>,
+	retries: u64,
+	cache_hits: u64,
+	latency: Histogram,
+	rate_limit_wait: Histogram,
+}
+
+impl EndpointMetrics {
+	/// Returns the number of HTTP requests sent, including retries and failovers.
+	#[must_use]
+	pub const fn get_requests(&self) -> u64 {
+		self.requests
+	}
+
+	/// Returns the number of failed requests and unparseable responses by `MidgardError::kind`.
+	#[must_use]
+	pub const fn get_errors(&self) -> &BTreeMap<&'static str, u64> {
+		&self.errors
+	}
+
+	/// Returns the total number of errors of all kinds.
+	#[must_use]
+	pub fn get_error_count(&self) -> u64 {
+		self.errors.values().sum()
+	}
+
+	#[must_use]
+	pub const fn get_retries(&self) -> u64 {
+		self.retries
+	}
+
+	/// Returns the number of calls served from the response cache, without a request.
+	#[must_use]
+	pub const fn get_cache_hits(&self) -> u64 {
+		self.cache_hits
+	}
+
+	/// Returns the latencies of the requests, from sending until the body was read or the request failed.
+	#[must_use]
+	pub const fn get_latency(&self) -> &Histogram {
+		&self.latency
+	}
+
+	/// Returns the time each request spent blocked in the rate limiter before it was sent.
+	#[must_use]
+	pub const fn get_rate_limit_wait(&self) -> &Histogram {
+		&self.rate_limit_wait
+	}
+
+	fn merge(&mut self, other: &Self) {
+		self.requests += other.requests;
+		for (kind, count) in &other.errors {
+			*self.errors.entry(kind).or_default() += count;
+		}
+		self.retries += other.retries;
+		self.cache_hits += other.cache_hits;
+		self.latency.merge(&other.latency);
+		self.rate_limit_wait.merge(&other.rate_limit_wait);
+	}
+}
+
+/// The metrics collected by a `Midgard` instance and its clones, as returned by `Midgard::get_metrics`.
+///
+/// Metrics are kept per endpoint path, such as `pools` or `pool/BTC.BTC/stats`, without the query string.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+	endpoints: HashMap<String, EndpointMetrics>,
+}
+
+impl MetricsSnapshot {
+	/// Returns the metrics of every endpoint path that has been called.
+	#[must_use]
+	pub const fn get_endpoints(&self) -> &HashMap<String, EndpointMetrics> {
+		&self.endpoints
+	}
+
+	/// Returns the metrics of an endpoint path, all zero if it hasn't been called. A query string is ignored.
+	#[must_use]
+	pub fn get_endpoint(&self, endpoint: &str) -> EndpointMetrics {
+		self.endpoints.get(endpoint_path(endpoint)).cloned().unwrap_or_default()
+	}
+
+	/// Returns the metrics summed over the endpoints of a group.
+	#[must_use]
+	pub fn get_group(&self, group: EndpointGroup) -> EndpointMetrics {
+		sum(self.endpoints.iter().filter(|(endpoint, _)| EndpointGroup::of(endpoint) == Some(group)).map(|(_, metrics)| metrics))
+	}
+
+	/// Returns the metrics summed over all endpoints.
+	#[must_use]
+	pub fn get_total(&self) -> EndpointMetrics {
+		sum(self.endpoints.values())
+	}
+}
+
+fn sum<'a>(metrics: impl Iterator<Item = &'a EndpointMetrics>) -> EndpointMetrics {
+	metrics.fold(EndpointMetrics::default(), |mut total, metrics| {
+		total.merge(metrics);
+		total
+	})
+}
+
+/// Strips the query string from an endpoint.
+fn endpoint_path(endpoint: &str) -> &str {
+	endpoint.split_once('?').map_or(endpoint, |(path, _)| path)
+}
+
+/// Collects `EndpointMetrics` per endpoint path, shared by a `Midgard` instance and all of its clones.
+/// With the `metrics` feature every measurement is also sent to the recorder installed for the `metrics` crate, labelled by endpoint group to keep the number of series bounded.
+#[derive(Debug, Clone, Default)]
+pub struct RequestMetrics {
+	endpoints: Arc<Mutex<HashMap<String, EndpointMetrics>>>,
+}
+
+impl RequestMetrics {
+	pub fn snapshot(&self) -> MetricsSnapshot {
+		MetricsSnapshot { endpoints: self.lock().clone() }
+	}
+
+	pub fn reset(&self) {
+		self.lock().clear();
+	}
+
+	/// Records a request and its outcome.
+	pub fn record_request(&self, endpoint: &str, latency: Duration, error: Option<&MidgardError>) {
+		self.update(endpoint, |metrics| {
+			metrics.requests += 1;
+			metrics.latency.record(latency);
+		});
+		#[cfg(feature = "metrics")]
+		{
+			::metrics::counter!("midgard_requests_total", "group" => group_label(endpoint)).increment(1);
+			::metrics::histogram!("midgard_request_duration_seconds", "group" => group_label(endpoint)).record(latency.as_secs_f64());
+		}
+		if let Some(error) = error {
+			self.record_error(endpoint, error);
+		}
+	}
+
+	/// Records the time a request spent waiting for the rate limiter before it was sent.
+	pub fn record_rate_limit_wait(&self, endpoint: &str, wait: Duration) {
+		self.update(endpoint, |metrics| metrics.rate_limit_wait.record(wait));
+		#[cfg(feature = "metrics")]
+		::metrics::histogram!("midgard_rate_limit_wait_seconds", "group" => group_label(endpoint)).record(wait.as_secs_f64());
+	}
+
+	/// Records a failed request or a response that couldn't be parsed.
+	pub fn record_error(&self, endpoint: &str, error: &MidgardError) {
+		self.update(endpoint, |metrics| *metrics.errors.entry(error.kind()).or_default() += 1);
+		#[cfg(feature = "metrics")]
+		::metrics::counter!("midgard_errors_total", "group" => group_label(endpoint), "kind" => error.kind()).increment(1);
+	}
+
+	pub fn record_retry(&self, endpoint: &str) {
+		self.update(endpoint, |metrics| metrics.retries += 1);
+		#[cfg(feature = "metrics")]
+		::metrics::counter!("midgard_retries_total", "group" => group_label(endpoint)).increment(1);
+	}
+
+	pub fn record_cache_hit(&self, endpoint: &str) {
+		self.update(endpoint, |metrics| metrics.cache_hits += 1);
+		#[cfg(feature = "metrics")]
+		::metrics::counter!("midgard_cache_hits_total", "group" => group_label(endpoint)).increment(1);
+	}
+
+	fn update(&self, endpoint: &str, update: impl FnOnce(&mut EndpointMetrics)) {
+		update(self.lock().entry(endpoint_path(endpoint).to_string()).or_default());
+	}
+
+	fn lock(&self) -> MutexGuard<'_, HashMap<String, EndpointMetrics>> {
+		self.endpoints.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+}
+
+#[cfg(feature = "metrics")]
+fn group_label(endpoint: &str) -> &'static str {
+	EndpointGroup::of(endpoint).map_or("other", |group| group.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_histogram_buckets() {
+		let mut histogram = Histogram::default();
+		histogram.record(Duration::from_millis(3));
+		histogram.record(Duration::from_millis(10));
+		histogram.record(Duration::from_millis(70));
+		histogram.record(Duration::from_secs(45));
+
+		let buckets = histogram.get_buckets();
+		assert_eq!(buckets[0], (Some(Duration::from_millis(5)), 1));
+		assert_eq!(buckets[1], (Some(Duration::from_millis(10)), 2));
+		assert_eq!(buckets[4], (Some(Duration::from_millis(100)), 3));
+		assert_eq!(buckets[11], (Some(Duration::from_secs(30)), 3));
+		assert_eq!(buckets[12], (None, 4));
+		assert_eq!(histogram.get_sum(), Duration::from_millis(45_083));
+	}
+
+	#[test]
+	fn test_records_per_endpoint() {
+		let metrics = RequestMetrics::default();
+		let timeout = MidgardError::Timeout { endpoint: "pools".to_string() };
+		metrics.record_rate_limit_wait("pools", Duration::from_millis(20));
+		metrics.record_request("pools", Duration::from_millis(100), Some(&timeout));
+		metrics.record_retry("pools");
+		metrics.record_rate_limit_wait("pools?status=available", Duration::ZERO);
+		metrics.record_request("pools?status=available", Duration::from_millis(50), None);
+		metrics.record_request("pool/BTC.BTC", Duration::from_millis(30), None);
+		metrics.record_cache_hit("health");
+
+		let snapshot = metrics.snapshot();
+		let pools = snapshot.get_endpoint("pools");
+		assert_eq!(pools.get_requests(), 2);
+		assert_eq!(pools.get_errors().get("timeout"), Some(&1));
+		assert_eq!(pools.get_retries(), 1);
+		assert_eq!(pools.get_rate_limit_wait().get_sum(), Duration::from_millis(20));
+		assert_eq!(pools.get_latency().get_max(), Duration::from_millis(100));
+		assert_eq!(pools.get_latency().get_mean(), Duration::from_millis(75));
+		assert_eq!(snapshot.get_endpoint("pool/BTC.BTC").get_requests(), 1);
+		assert_eq!(snapshot.get_group(EndpointGroup::Pools).get_requests(), 3);
+		assert_eq!(snapshot.get_endpoint("health").get_cache_hits(), 1);
+		assert_eq!(snapshot.get_total().get_requests(), 3);
+
+		metrics.reset();
+		assert!(metrics.snapshot().get_endpoints().is_empty());
+	}
+}
@@ -0,0 +1,199 @@
+use std::time::Duration;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::MidgardError;
+
+/// Controls how failed requests are retried.
+///
+/// The delay before retry `n` (starting at 1) is `base_delay_ms * 2^(n - 1)`, capped at `max_delay_ms`.
+/// With `jitter` enabled a random delay between half and the full backoff is used instead, so concurrent clients don't retry in lockstep.
+/// A `Retry-After` header sent with a retryable response takes precedence over the computed backoff, within the same `max_delay_ms` cap.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct RetryPolicy {
+	/// Total number of attempts, including the first one. `1` disables retries.
+	max_attempts: u32,
+	base_delay_ms: u64,
+	max_delay_ms: u64,
+	jitter: bool,
+	/// HTTP status codes that are retried.
+	retry_statuses: Vec<u16>,
+	/// Whether timed out requests are retried.
+	retry_timeouts: bool,
+	/// Whether requests that failed before a response was received (connection refused, reset, ...) are retried.
+	retry_connection_errors: bool,
+	/// Whether `Retry-After` headers are honored.
+	respect_retry_after: bool,
+}
+
+impl RetryPolicy {
+	#[must_use]
+	pub fn new(max_attempts: u32, base_delay_ms: u64, max_delay_ms: u64) -> Self {
+		Self { max_attempts, base_delay_ms, max_delay_ms, ..Self::default() }
+	}
+
+	/// A policy that never retries.
+	#[must_use]
+	pub fn none() -> Self {
+		Self { max_attempts: 1, ..Self::default() }
+	}
+
+	#[must_use]
+	pub const fn get_max_attempts(&self) -> u32 {
+		self.max_attempts
+	}
+
+	#[must_use]
+	pub const fn get_base_delay_ms(&self) -> u64 {
+		self.base_delay_ms
+	}
+
+	#[must_use]
+	pub const fn get_max_delay_ms(&self) -> u64 {
+		self.max_delay_ms
+	}
+
+	#[must_use]
+	pub const fn get_jitter(&self) -> bool {
+		self.jitter
+	}
+
+	#[must_use]
+	pub fn get_retry_statuses(&self) -> &[u16] {
+		&self.retry_statuses
+	}
+
+	#[must_use]
+	pub const fn get_retry_timeouts(&self) -> bool {
+		self.retry_timeouts
+	}
+
+	#[must_use]
+	pub const fn get_retry_connection_errors(&self) -> bool {
+		self.retry_connection_errors
+	}
+
+	#[must_use]
+	pub const fn get_respect_retry_after(&self) -> bool {
+		self.respect_retry_after
+	}
+
+	pub const fn set_max_attempts(&mut self, max_attempts: u32) {
+		self.max_attempts = max_attempts;
+	}
+
+	pub const fn set_base_delay_ms(&mut self, base_delay_ms: u64) {
+		self.base_delay_ms = base_delay_ms;
+	}
+
+	pub const fn set_max_delay_ms(&mut self, max_delay_ms: u64) {
+		self.max_delay_ms = max_delay_ms;
+	}
+
+	pub const fn set_jitter(&mut self, jitter: bool) {
+		self.jitter = jitter;
+	}
+
+	pub fn set_retry_statuses(&mut self, retry_statuses: Vec<u16>) {
+		self.retry_statuses = retry_statuses;
+	}
+
+	pub const fn set_retry_timeouts(&mut self, retry_timeouts: bool) {
+		self.retry_timeouts = retry_timeouts;
+	}
+
+	pub const fn set_retry_connection_errors(&mut self, retry_connection_errors: bool) {
+		self.retry_connection_errors = retry_connection_errors;
+	}
+
+	pub const fn set_respect_retry_after(&mut self, respect_retry_after: bool) {
+		self.respect_retry_after = respect_retry_after;
+	}
+
+	/// Returns true if the error is worth another attempt under this policy.
+	#[must_use]
+	pub fn is_retryable(&self, error: &MidgardError) -> bool {
+		match error {
+			MidgardError::Timeout { .. } => self.retry_timeouts,
+			MidgardError::Request { .. } | MidgardError::Transport { .. } => self.retry_connection_errors && error.is_connection_error(),
+			MidgardError::Api { status, .. } | MidgardError::Http { status, .. } => self.retry_statuses.contains(status),
+			_ => false,
+		}
+	}
+
+	/// Returns the backoff to wait before retry number `retry` (starting at 1).
+	#[must_use]
+	pub fn backoff(&self, retry: u32) -> Duration {
+		let exponent = retry.saturating_sub(1).min(63);
+		let delay = self.base_delay_ms.saturating_mul(1_u64 << exponent).min(self.max_delay_ms);
+		let delay = if self.jitter && delay > 0 { rand::thread_rng().gen_range(delay / 2..=delay) } else { delay };
+		Duration::from_millis(delay)
+	}
+
+	/// Returns how long to wait before retry number `retry`, preferring the server's `Retry-After` when allowed.
+	pub(crate) fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+		match retry_after {
+			Some(retry_after) if self.respect_retry_after => retry_after.min(Duration::from_millis(self.max_delay_ms)),
+			_ => self.backoff(retry),
+		}
+	}
+}
+
+impl Default for RetryPolicy {
+	fn default() -> Self {
+		Self { max_attempts: 3, base_delay_ms: 500, max_delay_ms: 10_000, jitter: true, retry_statuses: vec![429, 502, 503, 504], retry_timeouts: true, retry_connection_errors: true, respect_retry_after: true }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_backoff_without_jitter() {
+		let mut policy = RetryPolicy::new(5, 100, 1_000);
+		policy.set_jitter(false);
+
+		assert_eq!(policy.backoff(1), Duration::from_millis(100));
+		assert_eq!(policy.backoff(2), Duration::from_millis(200));
+		assert_eq!(policy.backoff(3), Duration::from_millis(400));
+		assert_eq!(policy.backoff(5), Duration::from_secs(1));
+		assert_eq!(policy.backoff(100), Duration::from_secs(1));
+	}
+
+	#[test]
+	fn test_backoff_with_jitter() {
+		let policy = RetryPolicy::new(5, 100, 1_000);
+
+		for _ in 0..100 {
+			let delay = policy.backoff(3);
+			assert!(delay >= Duration::from_millis(200) && delay <= Duration::from_millis(400));
+		}
+	}
+
+	#[test]
+	fn test_is_retryable() {
+		let policy = RetryPolicy::default();
+
+		assert!(policy.is_retryable(&MidgardError::Timeout { endpoint: "health".to_string() }));
+		assert!(policy.is_retryable(&MidgardError::from_status("health", 429, "")));
+		assert!(policy.is_retryable(&MidgardError::from_status("health", 503, "")));
+		assert!(!policy.is_retryable(&MidgardError::from_status("pool/NOT.A.POOL", 404, r#"{"error":"not found"}"#)));
+		assert!(!policy.is_retryable(&MidgardError::InvalidParameter("count".to_string())));
+
+		// an invalid url is not a connection error
+		let invalid_url = reqwest::Client::new().get("not a url").build().unwrap_err();
+		assert!(!policy.is_retryable(&MidgardError::request("health", invalid_url)));
+	}
+
+	#[test]
+	fn test_retry_after_is_capped() {
+		let policy = RetryPolicy::new(3, 100, 1_000);
+
+		assert_eq!(policy.delay(1, Some(Duration::from_millis(200))), Duration::from_millis(200));
+		assert_eq!(policy.delay(1, Some(Duration::from_secs(100_000))), Duration::from_secs(1));
+	}
+}
@@ -0,0 +1,263 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use std::time::Duration;
+
+use futures::future::join_all;
+
+use crate::api::request_headers;
+use crate::runtime;
+use crate::{Configuration, FailoverPolicy, HealthInfo, HeightStamp, Routing, Transport, TransportRequest};
+
+/// Why an instance is currently not used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unhealthy {
+	/// A request failed with a connection error, a timeout or a 5xx status.
+	Unreachable,
+	/// `/health` reported `inSync: false`.
+	OutOfSync,
+	/// The scanner lags the most advanced instance by more than the allowed number of blocks.
+	Lagging,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Instance {
+	unhealthy: Option<Unhealthy>,
+	scanner_height: Option<u64>,
+	/// `lastCommitted` height reported by the last successful probe.
+	committed_height: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+	instances: HashMap<String, Instance>,
+	/// Incremented for every probe task started, a task stops once it is no longer the latest.
+	probe_task: u64,
+	/// Probe interval of the running probe task.
+	probe_interval_ms: Option<u64>,
+	/// Transport and configuration of the latest request, read by the probe task on every round.
+	probe_with: Option<(Arc<dyn Transport>, Configuration)>,
+	/// Highest committed height a request was served at. With `Routing::HighestCommitted` reads are never routed below it.
+	read_height: u64,
+}
+
+impl State {
+	fn ordered(&self, base_urls: &[&str]) -> Vec<String> {
+		let (healthy, unhealthy): (Vec<&str>, Vec<&str>) = base_urls.iter().partition(|url| self.instances.get(**url).is_none_or(|instance| instance.unhealthy.is_none()));
+		healthy.into_iter().chain(unhealthy).map(str::to_string).collect()
+	}
+
+	fn highest_committed(&self, base_urls: &[&str], max_lag: u64) -> Vec<String> {
+		let instances: Vec<(&str, &Instance)> = base_urls.iter().filter_map(|url| self.instances.get(*url).map(|instance| (*url, instance))).collect();
+		let highest = instances.iter().filter_map(|(_, instance)| instance.committed_height).max().unwrap_or_default();
+		let min_height = highest.saturating_sub(max_lag).max(self.read_height);
+		let mut eligible: Vec<(&str, &Instance)> = instances.into_iter().filter(|(_, instance)| instance.committed_height.is_some_and(|height| height >= min_height)).collect();
+		eligible.sort_by_key(|(_, instance)| (instance.unhealthy.is_some(), Reverse(instance.committed_height)));
+		eligible.into_iter().map(|(url, _)| url.to_string()).collect()
+	}
+}
+
+/// Health and heights of the configured Midgard instances, shared by a `Midgard` instance and all of its clones.
+#[derive(Debug, Clone, Default)]
+pub struct Router {
+	state: Arc<Mutex<State>>,
+}
+
+impl Router {
+	/// Orders `base_urls` for a request, healthy instances first and the unhealthy ones as a last resort.
+	///
+	/// With `Routing::Ordered` instances keep their configured order. With `Routing::HighestCommitted` they are ordered by committed height,
+	/// and instances that are more than `max_lag` blocks behind the highest one, behind the previous read or not probed yet are left out, so the list may be empty.
+	pub fn candidates(&self, base_urls: &[&str], routing: Routing) -> Vec<String> {
+		match routing {
+			Routing::Ordered => self.lock().ordered(base_urls),
+			Routing::HighestCommitted { max_lag } => self.lock().highest_committed(base_urls, max_lag),
+		}
+	}
+
+	/// Returns the highest committed height a request has been served at.
+	pub fn read_height(&self) -> u64 {
+		self.lock().read_height
+	}
+
+	pub fn mark_unreachable(&self, base_url: &str) {
+		self.lock().instances.entry(base_url.to_string()).or_default().unhealthy = Some(Unhealthy::Unreachable);
+	}
+
+	/// Records a successful request. Instances that were only unreachable become healthy again; health problems reported by `/health` are left to the next probe.
+	/// The instance's committed height becomes the minimum height of later reads.
+	pub fn mark_reachable(&self, base_url: &str) {
+		let mut state = self.lock();
+		let Some(instance) = state.instances.get_mut(base_url) else {
+			return;
+		};
+		if instance.unhealthy == Some(Unhealthy::Unreachable) {
+			instance.unhealthy = None;
+		}
+		if let Some(height) = instance.committed_height {
+			state.read_height = state.read_height.max(height);
+		}
+	}
+
+	/// Updates the health of every instance from one round of `/health` probes. `None` means the probe failed.
+	fn record_probes(&self, probes: Vec<(String, Option<HealthInfo>)>, policy: &FailoverPolicy) {
+		let mut state = self.lock();
+		for (base_url, health) in probes {
+			let instance = state.instances.entry(base_url).or_default();
+			match health {
+				Some(health) => {
+					instance.scanner_height = *health.get_scanner_height();
+					instance.committed_height = health.get_last_committed().as_ref().map(HeightStamp::get_height);
+					instance.unhealthy = (health.get_in_sync() == Some(false)).then_some(Unhealthy::OutOfSync);
+				}
+				None => instance.unhealthy = Some(Unhealthy::Unreachable),
+			}
+		}
+
+		let highest = state.instances.values().filter_map(|instance| instance.scanner_height).max().unwrap_or_default();
+		for instance in state.instances.values_mut() {
+			let lagging = instance.scanner_height.is_some_and(|height| highest.saturating_sub(height) > policy.get_max_scanner_lag());
+			if lagging && instance.unhealthy.is_none() {
+				instance.unhealthy = Some(Unhealthy::Lagging);
+			}
+		}
+	}
+
+	/// Probes all base urls of `config` concurrently and records the results.
+	pub async fn probe_all(&self, transport: &Arc<dyn Transport>, config: &Configuration) {
+		let probes = join_all(config.get_base_urls().into_iter().map(|base_url| async move { (base_url.to_string(), probe(transport.as_ref(), config, base_url).await) })).await;
+		self.record_probes(probes, config.get_failover_policy());
+	}
+
+	/// Starts probing the base urls of `config` in the background.
+	/// A running probe task switches to `transport` and `config` from its next round on, so changes made with `Midgard::set_config` reach it with the next request.
+	/// It is replaced by a new task when the probe interval changed.
+	/// The task ends once the `Midgard` instance and all of its clones have been dropped.
+	pub fn start_probing(&self, transport: &Arc<dyn Transport>, config: Configuration) {
+		let interval_ms = config.get_failover_policy().get_probe_interval_ms();
+		let task = {
+			let mut state = self.lock();
+			state.probe_with = Some((transport.clone(), config));
+			if state.probe_interval_ms == Some(interval_ms) {
+				return;
+			}
+			state.probe_interval_ms = Some(interval_ms);
+			state.probe_task += 1;
+			state.probe_task
+		};
+
+		let state = Arc::downgrade(&self.state);
+		runtime::spawn(async move {
+			loop {
+				runtime::sleep(Duration::from_millis(interval_ms)).await;
+				let Some(router) = Self::upgrade(&state) else {
+					break;
+				};
+				let Some((transport, config)) = router.probe_with(task) else {
+					break;
+				};
+				router.probe_all(&transport, &config).await;
+			}
+		});
+	}
+
+	/// Returns the transport and configuration to probe with, or `None` once `task` has been replaced by a newer probe task.
+	fn probe_with(&self, task: u64) -> Option<(Arc<dyn Transport>, Configuration)> {
+		let state = self.lock();
+		if state.probe_task == task {
+			state.probe_with.clone()
+		} else {
+			None
+		}
+	}
+
+	fn upgrade(state: &Weak<Mutex<State>>) -> Option<Self> {
+		state.upgrade().map(|state| Self { state })
+	}
+
+	fn lock(&self) -> MutexGuard<'_, State> {
+		self.state.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+}
+
+/// Fetches `/health` from an instance with the same headers, credentials and timeouts as any other request, so a hung instance fails its probe instead of stalling the round.
+/// Probes bypass the rate limiter, retries and middleware, they are few and spread over all instances.
+async fn probe(transport: &dyn Transport, config: &Configuration, base_url: &str) -> Option<HealthInfo> {
+	let headers = request_headers(config, &[]).ok()?;
+	let timeouts = config.get_timeouts();
+	let request = transport.send(TransportRequest::new("GET", base_url.to_string() + "health", headers, timeouts.read()));
+	let response = match timeouts.total() {
+		Some(total) => runtime::timeout(total, request).await.ok()?,
+		None => request.await,
+	}
+	.ok()?;
+	if !(200..300).contains(&response.get_status()) {
+		return None;
+	}
+	serde_json::from_slice(response.get_body()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn health(in_sync: bool, scanner_height: u64) -> Option<HealthInfo> {
+		serde_json::from_str(&format!(r#"{{"database":true,"inSync":{in_sync},"scannerHeight":"{scanner_height}"}}"#)).ok()
+	}
+
+	fn committed(height: u64) -> Option<HealthInfo> {
+		serde_json::from_str(&format!(r#"{{"database":true,"inSync":true,"scannerHeight":"{height}","lastCommitted":{{"height":{height},"timestamp":0}}}}"#)).ok()
+	}
+
+	#[test]
+	fn test_candidates_skip_unreachable_instances() {
+		let router = Router::default();
+		let base_urls = ["https://a/v2/", "https://b/v2/", "https://c/v2/"];
+
+		assert_eq!(router.candidates(&base_urls, Routing::Ordered), base_urls);
+
+		router.mark_unreachable("https://a/v2/");
+		assert_eq!(router.candidates(&base_urls, Routing::Ordered), ["https://b/v2/", "https://c/v2/", "https://a/v2/"]);
+
+		router.mark_reachable("https://a/v2/");
+		assert_eq!(router.candidates(&base_urls, Routing::Ordered), base_urls);
+	}
+
+	#[test]
+	fn test_probes_mark_out_of_sync_and_lagging_instances() {
+		let router = Router::default();
+		let base_urls = ["https://a/v2/", "https://b/v2/", "https://c/v2/", "https://d/v2/"];
+		let probes = vec![("https://a/v2/".to_string(), health(false, 1_000)), ("https://b/v2/".to_string(), health(true, 800)), ("https://c/v2/".to_string(), health(true, 995)), ("https://d/v2/".to_string(), None)];
+
+		router.record_probes(probes, &FailoverPolicy::new(30_000, 10));
+
+		assert_eq!(router.candidates(&base_urls, Routing::Ordered), ["https://c/v2/", "https://a/v2/", "https://b/v2/", "https://d/v2/"]);
+
+		// a successful request does not override what /health reported
+		router.mark_reachable("https://a/v2/");
+		assert_eq!(router.candidates(&base_urls, Routing::Ordered)[0], "https://c/v2/");
+	}
+
+	#[test]
+	fn test_highest_committed_routing() {
+		let router = Router::default();
+		let base_urls = ["https://a/v2/", "https://b/v2/", "https://c/v2/"];
+		let policy = FailoverPolicy::new(30_000, 1_000);
+		let routing = Routing::HighestCommitted { max_lag: 5 };
+
+		// instances are not used before their height is known
+		assert!(router.candidates(&base_urls, routing).is_empty());
+
+		router.record_probes(vec![("https://a/v2/".to_string(), committed(995)), ("https://b/v2/".to_string(), committed(1_000)), ("https://c/v2/".to_string(), committed(990))], &policy);
+		assert_eq!(router.candidates(&base_urls, routing), ["https://b/v2/", "https://a/v2/"]);
+
+		// after reading at 1_000, a lower height is not used anymore even within the allowed lag
+		router.mark_reachable("https://b/v2/");
+		assert_eq!(router.read_height(), 1_000);
+		router.mark_unreachable("https://b/v2/");
+		assert_eq!(router.candidates(&base_urls, routing), ["https://b/v2/"]);
+
+		router.record_probes(vec![("https://a/v2/".to_string(), committed(1_002)), ("https://b/v2/".to_string(), None), ("https://c/v2/".to_string(), committed(990))], &policy);
+		assert_eq!(router.candidates(&base_urls, routing), ["https://a/v2/", "https://b/v2/"]);
+	}
+}
@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Timeouts applied to every request. `None` disables a timeout.
+///
+/// * `connect_ms` limits establishing a connection. It is set on the HTTP client built by `Midgard::new` and `Midgard::with_config`; a client passed to `Midgard::with_client` keeps its own connect timeout. It is ignored on wasm32, where the browser opens the connections.
+/// * `read_ms` limits the wait for the response headers and for each chunk of the body.
+/// * `total_ms` limits a whole attempt, from sending the request to reading the last byte of the body. Rate limit waits and retries are not included.
+///
+/// A request exceeding any of them fails with `MidgardError::Timeout`, which is retried according to the `RetryPolicy`.
+///
+/// # Example
+///
+/// ```rust
+/// use midgard_rs::{Configuration, Midgard, Timeouts};
+///
+/// let mut config = Configuration::default();
+/// config.set_timeouts(Timeouts::new(Some(5_000), Some(10_000), Some(30_000))); // connect_ms, read_ms, total_ms
+/// let midgard = Midgard::with_config(config);
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+#[allow(clippy::struct_field_names)]
+pub struct Timeouts {
+	connect_ms: Option<u64>,
+	read_ms: Option<u64>,
+	total_ms: Option<u64>,
+}
+
+impl Timeouts {
+	#[must_use]
+	pub const fn new(connect_ms: Option<u64>, read_ms: Option<u64>, total_ms: Option<u64>) -> Self {
+		Self { connect_ms, read_ms, total_ms }
+	}
+
+	/// Timeouts that never expire.
+	#[must_use]
+	pub const fn none() -> Self {
+		Self { connect_ms: None, read_ms: None, total_ms: None }
+	}
+
+	#[must_use]
+	pub const fn get_connect_ms(&self) -> Option<u64> {
+		self.connect_ms
+	}
+
+	#[must_use]
+	pub const fn get_read_ms(&self) -> Option<u64> {
+		self.read_ms
+	}
+
+	#[must_use]
+	pub const fn get_total_ms(&self) -> Option<u64> {
+		self.total_ms
+	}
+
+	pub const fn set_connect_ms(&mut self, connect_ms: Option<u64>) {
+		self.connect_ms = connect_ms;
+	}
+
+	pub const fn set_read_ms(&mut self, read_ms: Option<u64>) {
+		self.read_ms = read_ms;
+	}
+
+	pub const fn set_total_ms(&mut self, total_ms: Option<u64>) {
+		self.total_ms = total_ms;
+	}
+
+	pub(crate) const fn connect(&self) -> Option<Duration> {
+		match self.connect_ms {
+			Some(ms) => Some(Duration::from_millis(ms)),
+			None => None,
+		}
+	}
+
+	pub(crate) const fn read(&self) -> Option<Duration> {
+		match self.read_ms {
+			Some(ms) => Some(Duration::from_millis(ms)),
+			None => None,
+		}
+	}
+
+	pub(crate) const fn total(&self) -> Option<Duration> {
+		match self.total_ms {
+			Some(ms) => Some(Duration::from_millis(ms)),
+			None => None,
+		}
+	}
+}
+
+impl Default for Timeouts {
+	fn default() -> Self {
+		Self { connect_ms: Some(10_000), read_ms: Some(30_000), total_ms: Some(60_000) }
+	}
+}
@@ -0,0 +1,257 @@
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use futures::future::BoxFuture;
+#[cfg(target_arch = "wasm32")]
+use futures::future::LocalBoxFuture;
+use reqwest::header::HeaderMap;
+use reqwest::Client;
+use thiserror::Error;
+
+use crate::runtime;
+
+/// Sends the HTTP requests of a `Midgard` instance.
+///
+/// `Midgard` uses `ReqwestTransport` by default. Another backend, a test double or an in-process service can be plugged in with `Midgard::with_transport`;
+/// rate limits, retries, failover, caching and middleware work the same on top of every transport.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+///
+/// use futures::future::BoxFuture;
+/// use midgard_rs::{Configuration, Midgard, Transport, TransportError, TransportRequest, TransportResponse};
+///
+/// /// Answers every request with the same body.
+/// #[derive(Debug)]
+/// struct Canned(&'static str);
+///
+/// impl Transport for Canned {
+///     fn send(&self, _request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+///         Box::pin(async move { Ok(TransportResponse::new(200, Default::default(), self.0.as_bytes().to_vec())) })
+///     }
+/// }
+///
+/// # tokio_test::block_on(async {
+/// let midgard = Midgard::with_transport(Configuration::new("http://midgard/v2/".to_string(), 0), Arc::new(Canned(r#"{"database":true,"inSync":true,"scannerHeight":"1"}"#)));
+/// assert_eq!(midgard.get_health_info().await.unwrap().get_in_sync(), Some(true));
+/// # });
+/// ```
+pub trait Transport: fmt::Debug + Send + Sync {
+	/// Sends `request` and reads the whole response, whatever its status.
+	/// Implementations should fail with `TransportError::Timeout` when the read timeout of the request passes without progress; the total timeout is enforced by `Midgard`.
+	fn send(&self, request: TransportRequest) -> TransportFuture<'_>;
+}
+
+/// The future returned by `Transport::send`: a `BoxFuture`, or a `LocalBoxFuture` on wasm32 where the browser fetch API can't be used from another thread.
+#[cfg(not(target_arch = "wasm32"))]
+pub type TransportFuture<'a> = BoxFuture<'a, Result<TransportResponse, TransportError>>;
+
+/// The future returned by `Transport::send`: a `BoxFuture`, or a `LocalBoxFuture` on wasm32 where the browser fetch API can't be used from another thread.
+#[cfg(target_arch = "wasm32")]
+pub type TransportFuture<'a> = LocalBoxFuture<'a, Result<TransportResponse, TransportError>>;
+
+/// An HTTP request handed to a `Transport`.
+#[derive(Debug, Clone)]
+pub struct TransportRequest {
+	method: String,
+	url: String,
+	/// Authentication headers are marked sensitive and redacted in `Debug` output.
+	headers: HeaderMap,
+	read_timeout: Option<Duration>,
+}
+
+impl TransportRequest {
+	#[must_use]
+	pub fn new(method: &str, url: String, headers: HeaderMap, read_timeout: Option<Duration>) -> Self {
+		Self { method: method.to_string(), url, headers, read_timeout }
+	}
+
+	#[must_use]
+	pub fn get_method(&self) -> &str {
+		&self.method
+	}
+
+	#[must_use]
+	pub fn get_url(&self) -> &str {
+		&self.url
+	}
+
+	#[must_use]
+	pub const fn get_headers(&self) -> &HeaderMap {
+		&self.headers
+	}
+
+	/// Returns the longest time to wait for the response headers or the next chunk of the body.
+	#[must_use]
+	pub const fn get_read_timeout(&self) -> Option<Duration> {
+		self.read_timeout
+	}
+}
+
+/// An HTTP response returned by a `Transport`.
+#[derive(Debug, Clone)]
+pub struct TransportResponse {
+	status: u16,
+	headers: HeaderMap,
+	body: Vec<u8>,
+}
+
+impl TransportResponse {
+	#[must_use]
+	pub const fn new(status: u16, headers: HeaderMap, body: Vec<u8>) -> Self {
+		Self { status, headers, body }
+	}
+
+	#[must_use]
+	pub const fn get_status(&self) -> u16 {
+		self.status
+	}
+
+	#[must_use]
+	pub const fn get_headers(&self) -> &HeaderMap {
+		&self.headers
+	}
+
+	#[must_use]
+	pub fn get_body(&self) -> &[u8] {
+		&self.body
+	}
+
+	#[must_use]
+	pub fn into_body(self) -> Vec<u8> {
+		self.body
+	}
+}
+
+/// Why a `Transport` couldn't deliver a response.
+#[derive(Error, Debug)]
+pub enum TransportError {
+	/// The request did not complete in time. Reported as `MidgardError::Timeout`.
+	#[error("timed out")]
+	Timeout,
+	/// The request could not be sent or the response could not be read. Reported as `MidgardError::Request` for `reqwest` errors and `MidgardError::Transport` otherwise.
+	#[error(transparent)]
+	Failed(Box<dyn Error + Send + Sync>),
+}
+
+/// The default `Transport`, sending requests with a `reqwest::Client`. On wasm32, `reqwest` sends them with the browser fetch API.
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+	client: Client,
+}
+
+impl ReqwestTransport {
+	#[must_use]
+	pub const fn new(client: Client) -> Self {
+		Self { client }
+	}
+
+	#[must_use]
+	pub const fn get_client(&self) -> &Client {
+		&self.client
+	}
+}
+
+impl Transport for ReqwestTransport {
+	fn send(&self, request: TransportRequest) -> TransportFuture<'_> {
+		Box::pin(async move {
+			let read_timeout = request.read_timeout;
+			let method = reqwest::Method::from_bytes(request.method.as_bytes()).map_err(|e| TransportError::Failed(Box::new(e)))?;
+			let response = within(read_timeout, self.client.request(method, request.url).headers(request.headers).send()).await?;
+
+			let status = response.status().as_u16();
+			let headers = response.headers().clone();
+			Ok(TransportResponse::new(status, headers, read_body(response, read_timeout).await?))
+		})
+	}
+}
+
+/// Reads the body chunk by chunk, the read timeout applying to each chunk.
+#[cfg(not(target_arch = "wasm32"))]
+async fn read_body(mut response: reqwest::Response, read_timeout: Option<Duration>) -> Result<Vec<u8>, TransportError> {
+	let mut body = vec![];
+	while let Some(chunk) = within(read_timeout, response.chunk()).await? {
+		body.extend_from_slice(&chunk);
+	}
+	Ok(body)
+}
+
+/// Reads the body at once: fetch responses can't be read chunk by chunk, so the read timeout applies to the whole body.
+#[cfg(target_arch = "wasm32")]
+async fn read_body(response: reqwest::Response, read_timeout: Option<Duration>) -> Result<Vec<u8>, TransportError> {
+	Ok(within(read_timeout, response.bytes()).await?.to_vec())
+}
+
+/// Awaits a step of a request, failing with `TransportError::Timeout` if it takes longer than `timeout`.
+async fn within<T>(timeout: Option<Duration>, step: impl Future<Output = Result<T, reqwest::Error>>) -> Result<T, TransportError> {
+	let result = match timeout {
+		Some(timeout) => runtime::timeout(timeout, step).await.map_err(|_| TransportError::Timeout)?,
+		None => step.await,
+	};
+	result.map_err(|e| if e.is_timeout() { TransportError::Timeout } else { TransportError::Failed(Box::new(e)) })
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io;
+	use std::sync::{Arc, Mutex};
+
+	use serde_json::Value;
+
+	use super::*;
+	use crate::{Configuration, Midgard, MidgardError, RetryPolicy};
+
+	/// Fails the first request, then answers with `{"ok":true}`. Records the requests it was given.
+	#[derive(Debug, Default)]
+	struct Flaky {
+		requests: Mutex<Vec<TransportRequest>>,
+	}
+
+	impl Transport for Flaky {
+		fn send(&self, request: TransportRequest) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+			Box::pin(async move {
+				let attempt = {
+					let mut requests = self.requests.lock().unwrap();
+					requests.push(request);
+					requests.len()
+				};
+				if attempt == 1 {
+					return Err(TransportError::Failed(Box::new(io::Error::new(io::ErrorKind::ConnectionReset, "reset"))));
+				}
+				Ok(TransportResponse::new(200, HeaderMap::new(), br#"{"ok":true}"#.to_vec()))
+			})
+		}
+	}
+
+	#[tokio::test]
+	async fn test_sends_through_custom_transport() {
+		let transport = Arc::new(Flaky::default());
+		let mut policy = RetryPolicy::new(2, 1, 10);
+		policy.set_jitter(false);
+		let mut config = Configuration::new("http://midgard/v2/".to_string(), 0);
+		config.set_retry_policy(policy);
+		config.set_header("x-client-id", "tests");
+		let midgard = Midgard::with_transport(config, transport.clone());
+
+		let res: Value = crate::api::get_json(&midgard, "health").await.unwrap();
+		assert_eq!(res["ok"], true);
+
+		let requests = transport.requests.lock().unwrap().clone();
+		assert_eq!(requests.len(), 2);
+		assert_eq!(requests[1].get_url(), "http://midgard/v2/health");
+		assert_eq!(requests[1].get_headers()["x-client-id"], "tests");
+	}
+
+	#[test]
+	fn test_maps_transport_errors() {
+		let error = MidgardError::transport("health", TransportError::Failed(Box::new(io::Error::other("refused"))));
+		assert!(matches!(error, MidgardError::Transport { .. }));
+		assert_eq!(error.endpoint(), Some("health"));
+		assert!(MidgardError::transport("health", TransportError::Timeout).is_timeout());
+	}
+}
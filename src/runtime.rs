@@ -0,0 +1,119 @@
+//! The async runtime the client runs on: `tokio` with the `tokio` feature, the browser event loop on wasm32 with the `wasm` feature.
+//! Sleeping, timeouts, background tasks, clocks and file access all go through this module, so the rest of the crate is runtime agnostic.
+
+pub use imp::*;
+
+#[cfg(not(any(feature = "tokio", all(feature = "wasm", target_arch = "wasm32"))))]
+compile_error!("midgard-rs needs an async runtime: enable the `tokio` feature, or the `wasm` feature when building for wasm32");
+
+/// The future passed to `timeout` did not complete in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+#[cfg(all(feature = "tokio", not(all(feature = "wasm", target_arch = "wasm32"))))]
+mod imp {
+	use std::future::Future;
+	use std::time::Duration;
+
+	pub use std::time::Instant;
+
+	pub use tokio::fs::{create_dir_all, read, rename, write};
+
+	use super::Elapsed;
+
+	pub async fn sleep(duration: Duration) {
+		tokio::time::sleep(duration).await;
+	}
+
+	/// Awaits `future`, giving up once `duration` has passed.
+	pub async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, Elapsed> {
+		tokio::time::timeout(duration, future).await.map_err(|_| Elapsed)
+	}
+
+	/// Runs `future` in the background. The task is detached: it ends on its own.
+	pub fn spawn(future: impl Future<Output = ()> + Send + 'static) {
+		tokio::spawn(future);
+	}
+}
+
+// the file system stubs keep the async signatures of `tokio::fs`
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+#[allow(clippy::unused_async)]
+mod imp {
+	use std::future::Future;
+	use std::io;
+	use std::path::Path;
+	use std::time::Duration;
+
+	use futures::future::{self, Either};
+	use wasm_bindgen::prelude::*;
+	pub use web_time::Instant;
+
+	use super::Elapsed;
+
+	#[wasm_bindgen]
+	extern "C" {
+		/// The global `setTimeout`, available in windows and workers alike.
+		#[wasm_bindgen(js_name = setTimeout)]
+		fn set_timeout(handler: &js_sys::Function, timeout: i32) -> JsValue;
+	}
+
+	pub async fn sleep(duration: Duration) {
+		let timeout = i32::try_from(duration.as_millis()).unwrap_or(i32::MAX);
+		let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+			set_timeout(&resolve, timeout);
+		});
+		// the promise only ever resolves
+		let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+	}
+
+	/// Awaits `future`, giving up once `duration` has passed.
+	pub async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, Elapsed> {
+		let future = std::pin::pin!(future);
+		let deadline = std::pin::pin!(sleep(duration));
+		match future::select(future, deadline).await {
+			Either::Left((output, _)) => Ok(output),
+			Either::Right(_) => Err(Elapsed),
+		}
+	}
+
+	/// Runs `future` on the browser event loop. The task is detached: it ends on its own.
+	pub fn spawn(future: impl Future<Output = ()> + 'static) {
+		wasm_bindgen_futures::spawn_local(future);
+	}
+
+	// Browsers have no file system: history stores and cassettes fail with `ErrorKind::Unsupported`.
+
+	pub async fn read(_path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+		Err(unsupported())
+	}
+
+	pub async fn write(_path: impl AsRef<Path>, _contents: impl AsRef<[u8]>) -> io::Result<()> {
+		Err(unsupported())
+	}
+
+	pub async fn create_dir_all(_path: impl AsRef<Path>) -> io::Result<()> {
+		Err(unsupported())
+	}
+
+	pub async fn rename(_from: impl AsRef<Path>, _to: impl AsRef<Path>) -> io::Result<()> {
+		Err(unsupported())
+	}
+
+	fn unsupported() -> io::Error {
+		io::Error::new(io::ErrorKind::Unsupported, "there is no file system on wasm32")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use super::*;
+
+	#[tokio::test]
+	async fn test_timeout() {
+		assert_eq!(timeout(Duration::from_secs(1), async { 1 }).await, Ok(1));
+		assert_eq!(timeout(Duration::from_millis(10), sleep(Duration::from_secs(1))).await, Err(Elapsed));
+	}
+}
@@ -0,0 +1,374 @@
+//! An offline Midgard for tests, enabled with the `testing` feature.
+//!
+//! `MockMidgard` is a local HTTP server answering every endpoint with a recorded response, so tests run deterministically and without network access.
+//! The recorded responses can be replaced per endpoint with `MockMidgard::set_response`, or with a sequence of `MockResponse`s carrying headers and delays with `MockMidgard::set_responses`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use midgard_rs::test_support::MockMidgard;
+//!
+//! # tokio_test::block_on(async {
+//! let server = MockMidgard::start().await;
+//! let midgard = server.get_midgard();
+//!
+//! let pools = midgard.get_pool_list(None, None).await.unwrap();
+//! assert!(!pools.get_pools().is_empty());
+//! assert_eq!(server.get_requests(), vec!["pools".to_string()]);
+//! # });
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use chrono::Utc;
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+use crate::{Configuration, Interval, Midgard};
+
+/// A response served by `MockMidgard` instead of the recorded one.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+	status: u16,
+	headers: Vec<(String, String)>,
+	body: String,
+	delay: Duration,
+}
+
+impl MockResponse {
+	#[must_use]
+	pub fn new(status: u16, body: &str) -> Self {
+		Self { status, headers: vec![], body: body.to_string(), delay: Duration::ZERO }
+	}
+
+	/// Adds a header to the response, such as `Retry-After`.
+	#[must_use]
+	pub fn with_header(mut self, name: &str, value: &str) -> Self {
+		self.headers.push((name.to_string(), value.to_string()));
+		self
+	}
+
+	/// Delays the response, to simulate a slow or hung server.
+	#[must_use]
+	pub const fn with_delay(mut self, delay: Duration) -> Self {
+		self.delay = delay;
+		self
+	}
+}
+
+/// A local HTTP server serving recorded Midgard responses. The server stops when it is dropped.
+///
+/// Connections are answered one at a time, in the order they arrive.
+#[derive(Debug)]
+pub struct MockMidgard {
+	base_url: String,
+	responses: Arc<Mutex<HashMap<String, VecDeque<MockResponse>>>>,
+	required_headers: Arc<Mutex<Vec<(String, String)>>>,
+	requests: Arc<Mutex<Vec<(String, String)>>>,
+	task: JoinHandle<()>,
+}
+
+impl MockMidgard {
+	/// Starts the server on a random local port. Must be called from within a tokio runtime.
+	///
+	/// # Panics
+	/// If no local port can be bound.
+	pub async fn start() -> Self {
+		let listener = TcpListener::bind("127.0.0.1:0").await.expect("failed to bind the mock Midgard server");
+		let base_url = format!("http://{}/v2/", listener.local_addr().expect("failed to read the address of the mock Midgard server"));
+		let responses: Arc<Mutex<HashMap<String, VecDeque<MockResponse>>>> = Arc::new(Mutex::new(HashMap::new()));
+		let required_headers: Arc<Mutex<Vec<(String, String)>>> = Arc::new(Mutex::new(vec![]));
+		let requests = Arc::new(Mutex::new(vec![]));
+
+		let task = tokio::spawn({
+			let responses = responses.clone();
+			let required_headers = required_headers.clone();
+			let requests = requests.clone();
+			async move {
+				while let Ok((mut stream, _)) = listener.accept().await {
+					let mut head = vec![];
+					let mut buf = [0_u8; 1024];
+					while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+						match stream.read(&mut buf).await {
+							Ok(0) | Err(_) => break,
+							Ok(n) => head.extend_from_slice(&buf[..n]),
+						}
+					}
+
+					let head = String::from_utf8_lossy(&head).into_owned();
+					let endpoint = head.split_whitespace().nth(1).unwrap_or_default().trim_start_matches("/v2/").to_string();
+					let authorized = required_headers.lock().unwrap_or_else(PoisonError::into_inner).iter().all(|(name, value)| head.lines().any(|line| line.split_once(':').is_some_and(|(line_name, line_value)| line_name.eq_ignore_ascii_case(name) && line_value.trim() == value)));
+					let response = if authorized {
+						next_response(&responses, endpoint.split('?').next().unwrap_or_default()).or_else(|| respond(&endpoint).map(|body| MockResponse::new(200, &body))).unwrap_or_else(|| MockResponse::new(404, r#"{"error":"unknown endpoint"}"#))
+					} else {
+						MockResponse::new(401, r#"{"error":"unauthorized"}"#)
+					};
+					requests.lock().unwrap_or_else(PoisonError::into_inner).push((endpoint, head));
+
+					tokio::time::sleep(response.delay).await;
+					let mut head = format!("HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n", response.status, response.body.len());
+					for (name, value) in &response.headers {
+						let _ = write!(head, "{name}: {value}\r\n");
+					}
+					head.push_str("\r\n");
+					let _ = stream.write_all(head.as_bytes()).await;
+					let _ = stream.write_all(response.body.as_bytes()).await;
+					let _ = stream.shutdown().await;
+				}
+			}
+		});
+
+		Self { base_url, responses, required_headers, requests, task }
+	}
+
+	#[must_use]
+	pub fn get_base_url(&self) -> &str {
+		&self.base_url
+	}
+
+	/// Returns a configuration pointing at the server, without rate limit.
+	#[must_use]
+	pub fn get_config(&self) -> Configuration {
+		Configuration::new(self.base_url.clone(), 0)
+	}
+
+	/// Returns a new client using `get_config`.
+	#[must_use]
+	pub fn get_midgard(&self) -> Midgard {
+		Midgard::with_config(self.get_config())
+	}
+
+	/// Returns the endpoints requested so far, relative to the base url and including the query string, in order.
+	#[must_use]
+	pub fn get_requests(&self) -> Vec<String> {
+		self.requests.lock().unwrap_or_else(PoisonError::into_inner).iter().map(|(endpoint, _)| endpoint.clone()).collect()
+	}
+
+	/// Returns the request line and headers of every request received so far, in order.
+	#[must_use]
+	pub fn get_request_heads(&self) -> Vec<String> {
+		self.requests.lock().unwrap_or_else(PoisonError::into_inner).iter().map(|(_, head)| head.clone()).collect()
+	}
+
+	/// Answers requests for `path` (relative to the base url, without query string) with `status` and `body` instead of the recorded response.
+	pub fn set_response(&self, path: &str, status: u16, body: &str) {
+		self.set_responses(path, vec![MockResponse::new(status, body)]);
+	}
+
+	/// Answers requests for `path` (relative to the base url, without query string) with `responses` in order, repeating the last one.
+	/// An empty list restores the recorded response.
+	pub fn set_responses(&self, path: &str, responses: Vec<MockResponse>) {
+		let mut overrides = self.responses.lock().unwrap_or_else(PoisonError::into_inner);
+		if responses.is_empty() {
+			overrides.remove(path);
+		} else {
+			overrides.insert(path.to_string(), responses.into());
+		}
+	}
+
+	/// Answers every request without the header `name: value` with `401 Unauthorized`, like an instance behind an authenticating proxy.
+	pub fn require_header(&self, name: &str, value: &str) {
+		self.required_headers.lock().unwrap_or_else(PoisonError::into_inner).push((name.to_string(), value.to_string()));
+	}
+}
+
+impl Drop for MockMidgard {
+	fn drop(&mut self) {
+		self.task.abort();
+	}
+}
+
+/// Takes the next response set for `path`, keeping the last one for later requests.
+fn next_response(responses: &Mutex<HashMap<String, VecDeque<MockResponse>>>, path: &str) -> Option<MockResponse> {
+	responses.lock().unwrap_or_else(PoisonError::into_inner).get_mut(path).and_then(|queue| if queue.len() > 1 { queue.pop_front() } else { queue.front().cloned() })
+}
+
+/// Returns the recorded response of an endpoint path such as `pool/BTC.BTC/stats`, or `None` for unknown endpoints.
+///
+/// Path parameters and the query string are ignored: every pool, address or name gets the same response.
+#[must_use]
+pub fn fixture(path: &str) -> Option<&'static str> {
+	let segments = path.split('?').next().unwrap_or_default().split('/').collect::<Vec<_>>();
+	let fixture = match segments.as_slice() {
+		["actions"] => include_str!("fixtures/actions.json"),
+		["balance", _] => include_str!("fixtures/balance.json"),
+		["borrower", _] => include_str!("fixtures/borrower.json"),
+		["borrowers"] => include_str!("fixtures/borrowers.json"),
+		["churns"] => include_str!("fixtures/churns.json"),
+		["health"] => include_str!("fixtures/health.json"),
+		["history", "depths", _] => include_str!("fixtures/history_depths.json"),
+		["history", "earnings"] => include_str!("fixtures/history_earnings.json"),
+		["history", "liquidity_changes", ..] => include_str!("fixtures/history_liquidity_changes.json"),
+		["history", "savers", _] => include_str!("fixtures/history_savers.json"),
+		["history", "swaps"] => include_str!("fixtures/history_swaps.json"),
+		["history", "tvl"] => include_str!("fixtures/history_tvl.json"),
+		["knownpools"] => include_str!("fixtures/knownpools.json"),
+		["member", _] => include_str!("fixtures/member.json"),
+		["members"] => include_str!("fixtures/members.json"),
+		["network"] => include_str!("fixtures/network.json"),
+		["nodes"] => include_str!("fixtures/nodes.json"),
+		["pool", _] => include_str!("fixtures/pool.json"),
+		["pool", _, "stats"] => include_str!("fixtures/pool_stats.json"),
+		["pools"] => include_str!("fixtures/pools.json"),
+		["saver", _] => include_str!("fixtures/saver.json"),
+		["stats"] => include_str!("fixtures/stats.json"),
+		["thorname", "lookup", _] => include_str!("fixtures/thorname_lookup.json"),
+		["thorname", "owner", _] => include_str!("fixtures/thorname_owner.json"),
+		["thorname", "rlookup", _] => include_str!("fixtures/thorname_rlookup.json"),
+		_ => return None,
+	};
+	Some(fixture)
+}
+
+/// Midgard returns at most this many history intervals per request.
+const MAX_PAGE: i64 = 400;
+
+/// Returns the response to `endpoint`: the recorded one, adapted to the query of history and action requests so paging works.
+fn respond(endpoint: &str) -> Option<String> {
+	let (path, query) = endpoint.split_once('?').unwrap_or((endpoint, ""));
+	let body = fixture(path)?;
+	let params = query.split('&').filter_map(|param| param.split_once('=')).collect::<HashMap<_, _>>();
+	let paged = if path.starts_with("history/") {
+		history_page(body, &params)
+	} else if path == "actions" {
+		actions_page(body, &params)
+	} else {
+		None
+	};
+	Some(paged.unwrap_or_else(|| body.to_string()))
+}
+
+/// Stretches a recorded history over the window asked for by `interval`, `count`, `from` and `to`.
+/// Every interval is a copy of the first recorded one with its start and end time moved. Months, quarters and years have a fixed length of 30, 91 and 365 days.
+fn history_page(body: &str, params: &HashMap<&str, &str>) -> Option<String> {
+	let step = match Interval::from(*params.get("interval")?) {
+		Interval::FiveMinutes => 300,
+		Interval::Hour => 3_600,
+		Interval::Day => 86_400,
+		Interval::Week => 7 * 86_400,
+		Interval::Month => 30 * 86_400,
+		Interval::Quarter => 91 * 86_400,
+		Interval::Year => 365 * 86_400,
+	};
+	let time = |name| params.get(name).and_then(|time| time.parse::<i64>().ok());
+	let count = time("count").map_or(MAX_PAGE, |count| count.min(MAX_PAGE));
+	let (from, to) = match (time("from"), time("to")) {
+		(Some(from), Some(to)) => (from, to.min(from + count * step)),
+		(Some(from), None) => (from, from + count * step),
+		(None, to) => {
+			let to = to.unwrap_or_else(|| Utc::now().timestamp());
+			(to - count * step, to)
+		}
+	};
+
+	let mut history = serde_json::from_str::<Value>(body).ok()?;
+	let template = history.get("intervals")?.get(0)?.clone();
+	let mut end = from;
+	let intervals = (from..to)
+		.step_by(usize::try_from(step).ok()?)
+		.map(|start| {
+			end = start + step;
+			let mut interval = template.clone();
+			interval["startTime"] = Value::String(start.to_string());
+			interval["endTime"] = Value::String(end.to_string());
+			interval
+		})
+		.collect();
+	history["intervals"] = Value::Array(intervals);
+	history["meta"]["startTime"] = Value::String(from.to_string());
+	history["meta"]["endTime"] = Value::String(end.to_string());
+	Some(history.to_string())
+}
+
+/// Repeats the recorded actions up to `limit` and hands out a new page token for every page.
+fn actions_page(body: &str, params: &HashMap<&str, &str>) -> Option<String> {
+	let limit = params.get("limit").and_then(|limit| limit.parse::<usize>().ok())?;
+	let mut page = serde_json::from_str::<Value>(body).ok()?;
+	let recorded = page.get("actions")?.as_array()?.clone();
+	page["actions"] = Value::Array(recorded.iter().cycle().take(limit).cloned().collect());
+	if let Some(token) = params.get("nextPageToken").and_then(|token| token.parse::<u64>().ok()) {
+		page["meta"]["nextPageToken"] = Value::String(token.saturating_sub(1).to_string());
+	}
+	if let Some(token) = params.get("prevPageToken").and_then(|token| token.parse::<u64>().ok()) {
+		page["meta"]["prevPageToken"] = Value::String((token + 1).to_string());
+	}
+	Some(page.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::TimeZone;
+	use serde::de::DeserializeOwned;
+
+	use super::*;
+	use crate::{ActionList, Balance, BorrowersDetails, BorrowersList, ChurnsList, DepthHistory, EarningsHistory, GlobalStats, HealthInfo, KnownPoolList, LiquidityChangeHistory, MemberDetails, MemberList, NetworkData, NodeList, Pool, PoolList, PoolStatistics, SaversDetails, SaversHistory, SwapHistory, TVLHistory, ThornameDetails, ThornameOwner, ThornameReverseLookup};
+
+	fn parses<T: DeserializeOwned>(path: &str) {
+		let body = fixture(path).unwrap_or_else(|| panic!("no fixture for {path}"));
+		if let Err(e) = serde_path_to_error::deserialize::<_, T>(&mut serde_json::Deserializer::from_str(body)) {
+			panic!("fixture for {path} doesn't parse: {e}");
+		}
+	}
+
+	#[test]
+	fn test_fixtures_parse() {
+		parses::<ActionList>("actions?asset=BTC.BTC&limit=10");
+		parses::<Balance>("balance/thor1");
+		parses::<BorrowersDetails>("borrower/bc1");
+		parses::<BorrowersList>("borrowers");
+		parses::<ChurnsList>("churns");
+		parses::<HealthInfo>("health");
+		parses::<DepthHistory>("history/depths/BTC.BTC");
+		parses::<EarningsHistory>("history/earnings");
+		parses::<LiquidityChangeHistory>("history/liquidity_changes");
+		parses::<SaversHistory>("history/savers/BTC.BTC");
+		parses::<SwapHistory>("history/swaps?pool=BTC.BTC");
+		parses::<TVLHistory>("history/tvl");
+		parses::<KnownPoolList>("knownpools");
+		parses::<MemberDetails>("member/thor1");
+		parses::<MemberList>("members");
+		parses::<NetworkData>("network");
+		parses::<NodeList>("nodes");
+		parses::<Pool>("pool/BTC.BTC");
+		parses::<PoolStatistics>("pool/BTC.BTC/stats");
+		parses::<PoolList>("pools");
+		parses::<SaversDetails>("saver/bc1");
+		parses::<GlobalStats>("stats");
+		parses::<ThornameDetails>("thorname/lookup/thorchain");
+		parses::<ThornameOwner>("thorname/owner/thor1");
+		parses::<ThornameReverseLookup>("thorname/rlookup/thor1");
+		assert!(fixture("unknown").is_none());
+	}
+
+	#[tokio::test]
+	async fn test_overrides_and_records_requests() {
+		let server = MockMidgard::start().await;
+		server.set_response("health", 503, r#"{"error":"down"}"#);
+		let mut config = server.get_config();
+		config.set_retry_policy(crate::RetryPolicy::none());
+		let midgard = Midgard::with_config(config);
+
+		assert_eq!(midgard.get_health_info().await.unwrap_err().status(), Some(503));
+		assert!(midgard.get_node_list().await.is_ok());
+		assert_eq!(server.get_requests(), vec!["health".to_string(), "nodes".to_string()]);
+	}
+
+	#[tokio::test]
+	async fn test_pages_history_and_actions() {
+		let server = MockMidgard::start().await;
+		let midgard = server.get_midgard();
+
+		let from = Utc.timestamp_opt(1_660_000_000, 0).unwrap();
+		let history = midgard.get_total_value_locked_history_range(Interval::Day, from..from + chrono::Duration::days(500)).await.unwrap();
+		assert_eq!(history.get_intervals().get_intervals().len(), 500);
+		assert_eq!(server.get_requests().len(), 2);
+
+		let actions = midgard.get_actions(crate::GetActionList::new(vec!["BTC.BTC".to_string()], 5)).await.unwrap();
+		assert_eq!(actions.get_actions().get_actions().len(), 5);
+	}
+}
@@ -0,0 +1,26 @@
+//! Spans and events emitted with the `tracing` feature. Without it the macros expand to the plain calls.
+
+/// Runs the future of a `Midgard::get_*` call in an `info` span named after the method, with the given parameter fields.
+/// The span also declares `endpoint`, `cache_hit`, `attempts` and `rate_limit_wait_ms`, which are recorded by `get_json`.
+macro_rules! traced {
+	($name:literal $(($($field:tt)+))?, $call:expr) => {{
+		#[cfg(feature = "tracing")]
+		let call = {
+			let span = tracing::info_span!($name, $($($field)+,)? endpoint = tracing::field::Empty, cache_hit = tracing::field::Empty, attempts = tracing::field::Empty, rate_limit_wait_ms = tracing::field::Empty);
+			tracing::Instrument::instrument($call, span)
+		};
+		#[cfg(not(feature = "tracing"))]
+		let call = $call;
+		call.await
+	}};
+}
+
+/// Emits a `tracing` event at the given level.
+macro_rules! event {
+	($level:ident, $($arg:tt)+) => {
+		#[cfg(feature = "tracing")]
+		tracing::$level!($($arg)+);
+	};
+}
+
+pub(crate) use {event, traced};
@@ -39,4 +39,10 @@ impl ActionList {
 	pub const fn get_meta(&self) -> &PageTokens {
 		&self.meta
 	}
+
+	/// Consumes the list, returning the actions of this page.
+	#[must_use]
+	pub fn into_actions(self) -> Actions {
+		self.actions
+	}
 }
@@ -16,8 +16,8 @@ use crate::DepthHistoryMeta;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct DepthHistory {
-	intervals: DepthHistoryIntervals,
-	meta: DepthHistoryMeta,
+	pub(crate) intervals: DepthHistoryIntervals,
+	pub(crate) meta: DepthHistoryMeta,
 }
 
 impl DepthHistory {
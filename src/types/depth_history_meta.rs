@@ -5,6 +5,8 @@ use serde_aux::prelude::*;
 use serde_with::formats::Flexible;
 use serde_with::TimestampSeconds;
 
+use crate::DepthHistoryInterval;
+
 /*
 
 *** DepthHistoryMeta Scheme ***
@@ -146,6 +148,65 @@ impl DepthHistoryMeta {
 	pub const fn get_start_time(&self) -> &DateTime<Utc> {
 		&self.start_time
 	}
+
+	/// Merges the meta of a later, adjacent page into this one.
+	/// Start values are kept, end values are taken from `next`, `luvi_increase` is compounded and `price_shift_loss` is recomputed from the start and end depths.
+	#[allow(clippy::needless_pass_by_value)]
+	pub fn merge(&mut self, next: Self) {
+		let luvi_increase = self.luvi_increase * next.luvi_increase;
+		*self = Self { luvi_increase, start_asset_depth: self.start_asset_depth, start_lp_units: self.start_lp_units, start_member_count: self.start_member_count, start_rune_depth: self.start_rune_depth, start_synth_units: self.start_synth_units, start_time: self.start_time, ..next };
+		if let Some(price_shift_loss) = self.compute_price_shift_loss() {
+			self.price_shift_loss = price_shift_loss;
+		}
+	}
+
+	/// Returns the meta of each of `intervals` as if it had been requested on its own, the inverse of `merge`.
+	/// The first interval starts with the start values of this meta, every later one with the end values of the interval before it.
+	/// `luvi_increase` is the ratio between the luvi of an interval and the one before it, the luvi before the first interval being implied by this meta's `luvi_increase`.
+	#[must_use]
+	pub fn split(&self, intervals: &[DepthHistoryInterval]) -> Vec<Self> {
+		let start_luvi = intervals.last().and_then(|last| last.get_luvi().checked_div(self.luvi_increase));
+		let previous = std::iter::once(None).chain(intervals.iter().map(Some));
+		intervals
+			.iter()
+			.zip(previous)
+			.map(|(interval, previous)| {
+				let start_luvi = previous.map_or(start_luvi, |previous| Some(*previous.get_luvi()));
+				let mut meta = Self {
+					end_asset_depth: *interval.get_asset_depth(),
+					end_lp_units: *interval.get_liquidity_units(),
+					end_member_count: *interval.get_members_count(),
+					end_rune_depth: *interval.get_rune_depth(),
+					end_synth_units: *interval.get_synth_units(),
+					end_time: *interval.get_end_time(),
+					luvi_increase: start_luvi.and_then(|start_luvi| interval.get_luvi().checked_div(start_luvi)).unwrap_or(Decimal::ONE),
+					price_shift_loss: Decimal::ONE,
+					start_asset_depth: previous.map_or(self.start_asset_depth, |previous| *previous.get_asset_depth()),
+					start_lp_units: previous.map_or(self.start_lp_units, |previous| *previous.get_liquidity_units()),
+					start_member_count: previous.map_or(self.start_member_count, |previous| *previous.get_members_count()),
+					start_rune_depth: previous.map_or(self.start_rune_depth, |previous| *previous.get_rune_depth()),
+					start_synth_units: previous.map_or(self.start_synth_units, |previous| *previous.get_synth_units()),
+					start_time: *interval.get_start_time(),
+				};
+				if let Some(price_shift_loss) = meta.compute_price_shift_loss() {
+					meta.price_shift_loss = price_shift_loss;
+				}
+				meta
+			})
+			.collect()
+	}
+
+	/// `2 * sqrt(r) / (1 + r)`, where `r` is the ratio between the end and start asset price in rune.
+	#[allow(clippy::cast_precision_loss)]
+	fn compute_price_shift_loss(&self) -> Option<Decimal> {
+		if self.start_asset_depth == 0 || self.start_rune_depth == 0 || self.end_asset_depth == 0 {
+			return None;
+		}
+		let start_price = self.start_rune_depth as f64 / self.start_asset_depth as f64;
+		let end_price = self.end_rune_depth as f64 / self.end_asset_depth as f64;
+		let ratio = end_price / start_price;
+		Decimal::try_from(2.0 * ratio.sqrt() / (1.0 + ratio)).ok()
+	}
 }
 
 #[cfg(test)]
@@ -192,4 +253,65 @@ mod tests {
 		let deserialized: DepthHistoryMeta = serde_json::from_str(json).unwrap();
 		assert_eq!(deserialized, expected);
 	}
+
+	#[test]
+	fn test_merge_depth_history_meta() {
+		let first = DepthHistoryMeta {
+			end_asset_depth: 20_500_000_000_000,
+			end_rune_depth: 3_600_000_000_000,
+			end_time: DateTime::from_timestamp(1_710_460_800, 0).expect("failed to create DateTime"),
+			luvi_increase: Decimal::new(105, 2),
+			start_asset_depth: 20_365_684_205_644,
+			start_rune_depth: 3_856_266_522_266,
+			start_time: DateTime::from_timestamp(1_710_028_800, 0).expect("failed to create DateTime"),
+			..Default::default()
+		};
+		let second = DepthHistoryMeta {
+			end_asset_depth: 20_789_919_007_903,
+			end_member_count: 202,
+			end_rune_depth: 3_324_827_631_133,
+			end_time: DateTime::from_timestamp(1_710_892_800, 0).expect("failed to create DateTime"),
+			luvi_increase: Decimal::new(2, 0),
+			start_asset_depth: 20_500_000_000_000,
+			start_rune_depth: 3_600_000_000_000,
+			start_time: DateTime::from_timestamp(1_710_460_800, 0).expect("failed to create DateTime"),
+			..Default::default()
+		};
+
+		let mut merged = first;
+		merged.merge(second);
+
+		assert_eq!(merged.get_start_time(), &DateTime::from_timestamp(1_710_028_800, 0).expect("failed to create DateTime"));
+		assert_eq!(merged.get_end_time(), &DateTime::from_timestamp(1_710_892_800, 0).expect("failed to create DateTime"));
+		assert_eq!(merged.get_start_asset_depth(), &20_365_684_205_644);
+		assert_eq!(merged.get_end_asset_depth(), &20_789_919_007_903);
+		assert_eq!(merged.get_end_member_count(), &202);
+		assert_eq!(merged.get_luvi_increase(), &Decimal::new(21, 1));
+		assert!((*merged.get_price_shift_loss() - Decimal::new(9_964_447_262_207_828, 16)).abs() < Decimal::new(1, 9));
+	}
+
+	#[test]
+	fn test_split_depth_history_meta() {
+		let interval = |start: i64, rune_depth: u64, luvi: &str| -> DepthHistoryInterval {
+			let json = format!(r#"{{"assetDepth":"100","assetPrice":"4","assetPriceUSD":"8","endTime":"{}","liquidityUnits":"50","luvi":"{luvi}","membersCount":"3","runeDepth":"{rune_depth}","startTime":"{start}","synthSupply":"0","synthUnits":"0","units":"50"}}"#, start + 86_400);
+			serde_json::from_str(&json).unwrap()
+		};
+		let intervals = vec![interval(0, 400, "1.5"), interval(86_400, 100, "3")];
+		let meta = DepthHistoryMeta { end_asset_depth: 100, end_rune_depth: 100, end_time: DateTime::from_timestamp(172_800, 0).expect("failed to create DateTime"), luvi_increase: Decimal::new(2, 0), start_asset_depth: 100, start_lp_units: 40, start_rune_depth: 400, ..Default::default() };
+
+		let split = meta.split(&intervals);
+
+		assert_eq!(split.len(), 2);
+		assert_eq!(split[0].get_start_time(), &DateTime::from_timestamp(0, 0).expect("failed to create DateTime"));
+		assert_eq!(split[0].get_end_time(), &DateTime::from_timestamp(86_400, 0).expect("failed to create DateTime"));
+		assert_eq!(split[0].get_start_lp_units(), &40);
+		assert_eq!(split[0].get_end_lp_units(), &50);
+		assert_eq!(split[0].get_luvi_increase(), &Decimal::ONE);
+		assert_eq!(split[0].get_price_shift_loss(), &Decimal::ONE);
+		assert_eq!(split[1].get_start_rune_depth(), &400);
+		assert_eq!(split[1].get_end_rune_depth(), &100);
+		assert_eq!(split[1].get_start_lp_units(), &50);
+		assert_eq!(split[1].get_luvi_increase(), &Decimal::new(2, 0));
+		assert!((*split[1].get_price_shift_loss() - Decimal::new(8, 1)).abs() < Decimal::new(1, 9));
+	}
 }
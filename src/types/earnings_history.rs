@@ -13,8 +13,8 @@ use crate::EarningsMeta;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct EarningsHistory {
-	intervals: EarningsIntervals,
-	meta: EarningsMeta,
+	pub(crate) intervals: EarningsIntervals,
+	pub(crate) meta: EarningsMeta,
 }
 
 impl EarningsHistory {
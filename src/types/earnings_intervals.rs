@@ -10,7 +10,7 @@ use crate::EarningsInterval;
 */
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
-pub struct EarningsIntervals(Vec<EarningsInterval>);
+pub struct EarningsIntervals(pub(crate) Vec<EarningsInterval>);
 
 impl EarningsIntervals {
 	#[must_use]
@@ -5,6 +5,7 @@ use serde_aux::prelude::*;
 use serde_with::formats::Flexible;
 use serde_with::TimestampSeconds;
 
+use crate::EarningsInterval;
 use crate::EarningsPools;
 
 /*
@@ -111,4 +112,41 @@ impl EarningsMeta {
 	pub const fn get_start_time(&self) -> &DateTime<Utc> {
 		&self.start_time
 	}
+
+	/// Merges the meta of a later, adjacent page into this one.
+	/// Earnings are summed (per pool as well), `avg_node_count` is averaged weighted by the duration of each page and the rune price is taken from `next`.
+	pub fn merge(&mut self, next: Self) {
+		let duration = Decimal::from((self.end_time - self.start_time).num_seconds().max(0));
+		let next_duration = Decimal::from((next.end_time - next.start_time).num_seconds().max(0));
+		let total_duration = duration + next_duration;
+		if !total_duration.is_zero() {
+			self.avg_node_count = (self.avg_node_count * duration + next.avg_node_count * next_duration) / total_duration;
+		}
+		self.block_rewards = self.block_rewards.saturating_add(next.block_rewards);
+		self.bonding_earnings = self.bonding_earnings.saturating_add(next.bonding_earnings);
+		self.earnings = self.earnings.saturating_add(next.earnings);
+		self.end_time = next.end_time;
+		self.liquidity_earnings = self.liquidity_earnings.saturating_add(next.liquidity_earnings);
+		self.liquidity_fees = self.liquidity_fees.saturating_add(next.liquidity_fees);
+		self.pools.merge(next.pools);
+		self.rune_price_usd = next.rune_price_usd;
+	}
+}
+
+/// The meta Midgard returns when `interval` is requested on its own.
+impl From<&EarningsInterval> for EarningsMeta {
+	fn from(interval: &EarningsInterval) -> Self {
+		Self {
+			avg_node_count: *interval.get_avg_node_count(),
+			block_rewards: *interval.get_block_rewards(),
+			bonding_earnings: *interval.get_bonding_earnings(),
+			earnings: *interval.get_earnings(),
+			end_time: *interval.get_end_time(),
+			liquidity_earnings: *interval.get_liquidity_earnings(),
+			liquidity_fees: *interval.get_liquidity_fees(),
+			pools: interval.get_pools().clone(),
+			rune_price_usd: *interval.get_rune_price_usd(),
+			start_time: *interval.get_start_time(),
+		}
+	}
 }
@@ -75,4 +75,15 @@ impl EarningsPool {
 	pub const fn get_total_liquidity_fees_rune(&self) -> &u64 {
 		&self.total_liquidity_fees_rune
 	}
+
+	/// Adds the earnings of another range for the same pool to this one.
+	#[allow(clippy::needless_pass_by_value)]
+	pub fn merge(&mut self, other: Self) {
+		self.asset_liquidity_fees = self.asset_liquidity_fees.saturating_add(other.asset_liquidity_fees);
+		self.earnings = self.earnings.saturating_add(other.earnings);
+		self.rewards = self.rewards.saturating_add(other.rewards);
+		self.rune_liquidity_fees = self.rune_liquidity_fees.saturating_add(other.rune_liquidity_fees);
+		self.saver_earning = self.saver_earning.saturating_add(other.saver_earning);
+		self.total_liquidity_fees_rune = self.total_liquidity_fees_rune.saturating_add(other.total_liquidity_fees_rune);
+	}
 }
@@ -22,6 +22,16 @@ impl EarningsPools {
 	pub fn is_empty(&self) -> bool {
 		self.0.is_empty()
 	}
+
+	/// Merges the earnings of another range into this one, matching pools by name.
+	pub fn merge(&mut self, other: Self) {
+		for pool in other.0 {
+			match self.0.iter_mut().find(|existing| existing.get_pool() == pool.get_pool()) {
+				Some(existing) => existing.merge(pool),
+				None => self.0.push(pool),
+			}
+		}
+	}
 }
 
 impl IntoIterator for EarningsPools {
@@ -1,11 +1,245 @@
 use thiserror::Error;
 
+use crate::TransportError;
+
+/// Maximum number of characters of a response body kept on an error.
+const BODY_SNIPPET_LEN: usize = 512;
+
 #[derive(Error, Debug)]
-pub enum APIError {
-	#[error("Reqwest Error: {0}")]
-	ReqwestError(#[from] reqwest::Error),
-	#[error("Serde Error: {0}")]
-	SerdeError(#[from] serde_json::Error),
+pub enum MidgardError {
+	/// The request could not be sent or the response body could not be read.
+	#[error("Request to {endpoint} failed: {source}")]
+	Request {
+		endpoint: String,
+		#[source]
+		source: reqwest::Error,
+	},
+	/// A custom `Transport` could not send the request or read the response.
+	#[error("Request to {endpoint} failed: {source}")]
+	Transport {
+		endpoint: String,
+		#[source]
+		source: Box<dyn std::error::Error + Send + Sync>,
+	},
+	/// The request did not complete in time.
+	#[error("Request to {endpoint} timed out")]
+	Timeout { endpoint: String },
+	/// Midgard answered with a non-2xx status and an error payload such as `{"error":"..."}`.
+	#[error("Midgard returned {status} for {endpoint}: {message}")]
+	Api { endpoint: String, status: u16, message: String },
+	/// Midgard answered with a non-2xx status and a body that is not a Midgard error payload.
+	#[error("Midgard returned {status} for {endpoint}")]
+	Http {
+		endpoint: String,
+		status: u16,
+		/// Truncated response body.
+		body: String,
+	},
+	/// The response body did not match the expected schema.
+	#[error("Failed to parse response from {endpoint} (status {status}) at `{path}`: {source}")]
+	Parse {
+		endpoint: String,
+		status: u16,
+		/// Serde path of the field that failed to parse, e.g. `intervals[3].startTime`.
+		path: String,
+		/// Truncated response body.
+		body: String,
+		#[source]
+		source: serde_json::Error,
+	},
+	/// With `Routing::HighestCommitted` no instance has caught up with the height of the previous read.
+	#[error("No Midgard instance has reached height {height} for {endpoint}")]
+	Inconsistent { endpoint: String, height: u64 },
+	/// The history store could not be read or written.
+	#[error("History store {path} failed: {source}")]
+	HistoryStore {
+		path: String,
+		#[source]
+		source: std::io::Error,
+	},
+	/// A cassette could not be read or written, or has no response recorded for a replayed request.
+	#[error("Cassette {path} failed: {source}")]
+	Cassette {
+		path: String,
+		#[source]
+		source: std::io::Error,
+	},
+	/// The query string could not be encoded.
+	#[error("Failed to encode query parameters: {0}")]
+	UrlEncode(#[from] serde_urlencoded::ser::Error),
 	#[error("Invalid Parameter: {0}")]
 	InvalidParameter(String),
 }
+
+impl MidgardError {
+	pub(crate) fn request(endpoint: &str, source: reqwest::Error) -> Self {
+		if source.is_timeout() {
+			Self::Timeout { endpoint: endpoint.to_string() }
+		} else {
+			Self::Request { endpoint: endpoint.to_string(), source }
+		}
+	}
+
+	/// Builds the error for a failed transport. Errors of the default reqwest transport keep their type.
+	pub(crate) fn transport(endpoint: &str, error: TransportError) -> Self {
+		match error {
+			TransportError::Timeout => Self::Timeout { endpoint: endpoint.to_string() },
+			TransportError::Failed(source) => match source.downcast::<reqwest::Error>() {
+				Ok(source) => Self::request(endpoint, *source),
+				Err(source) => Self::Transport { endpoint: endpoint.to_string(), source },
+			},
+		}
+	}
+
+	/// Builds the error for a non-2xx response, extracting the server message from Midgard's error payload when present.
+	pub(crate) fn from_status(endpoint: &str, status: u16, body: &str) -> Self {
+		#[derive(serde::Deserialize)]
+		struct ErrorPayload {
+			error: String,
+		}
+
+		match serde_json::from_str::<ErrorPayload>(body) {
+			Ok(payload) => Self::Api { endpoint: endpoint.to_string(), status, message: payload.error },
+			Err(_) => Self::Http { endpoint: endpoint.to_string(), status, body: snippet(body) },
+		}
+	}
+
+	pub(crate) fn parse(endpoint: &str, status: u16, body: &str, error: serde_path_to_error::Error<serde_json::Error>) -> Self {
+		Self::Parse { endpoint: endpoint.to_string(), status, path: error.path().to_string(), body: snippet(body), source: error.into_inner() }
+	}
+
+	/// Returns the HTTP status code of the response, if one was received.
+	#[must_use]
+	pub fn status(&self) -> Option<u16> {
+		match self {
+			Self::Request { source, .. } => source.status().map(|status| status.as_u16()),
+			Self::Api { status, .. } | Self::Http { status, .. } | Self::Parse { status, .. } => Some(*status),
+			_ => None,
+		}
+	}
+
+	/// Returns the endpoint path (including the query string) the error relates to.
+	#[must_use]
+	pub fn endpoint(&self) -> Option<&str> {
+		match self {
+			Self::Request { endpoint, .. } | Self::Transport { endpoint, .. } | Self::Timeout { endpoint } | Self::Api { endpoint, .. } | Self::Http { endpoint, .. } | Self::Parse { endpoint, .. } | Self::Inconsistent { endpoint, .. } => Some(endpoint),
+			_ => None,
+		}
+	}
+
+	/// Returns the (truncated) response body, if one was received.
+	#[must_use]
+	pub fn body(&self) -> Option<&str> {
+		match self {
+			Self::Http { body, .. } | Self::Parse { body, .. } => Some(body),
+			_ => None,
+		}
+	}
+
+	/// Returns the message from Midgard's error payload, if the server sent one.
+	#[must_use]
+	pub fn server_message(&self) -> Option<&str> {
+		match self {
+			Self::Api { message, .. } => Some(message),
+			_ => None,
+		}
+	}
+
+	/// Returns a short name for the kind of error, such as `timeout` or `api`, e.g. to label metrics.
+	#[must_use]
+	pub const fn kind(&self) -> &'static str {
+		match self {
+			Self::Request { .. } => "request",
+			Self::Transport { .. } => "transport",
+			Self::Timeout { .. } => "timeout",
+			Self::Api { .. } => "api",
+			Self::Http { .. } => "http",
+			Self::Parse { .. } => "parse",
+			Self::Inconsistent { .. } => "inconsistent",
+			Self::HistoryStore { .. } => "history_store",
+			Self::Cassette { .. } => "cassette",
+			Self::UrlEncode(_) => "url_encode",
+			Self::InvalidParameter(_) => "invalid_parameter",
+		}
+	}
+
+	#[must_use]
+	pub const fn is_timeout(&self) -> bool {
+		matches!(self, Self::Timeout { .. })
+	}
+
+	/// Returns true if the request failed before a response was received, e.g. a refused or reset connection.
+	/// Errors building the request, such as an invalid url, are not connection errors.
+	pub(crate) fn is_connection_error(&self) -> bool {
+		match self {
+			Self::Request { source, .. } => source.is_request() || source.is_body(),
+			Self::Transport { .. } => true,
+			_ => false,
+		}
+	}
+}
+
+fn snippet(body: &str) -> String {
+	match body.char_indices().nth(BODY_SNIPPET_LEN) {
+		Some((index, _)) => format!("{}...", &body[..index]),
+		None => body.to_string(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_error_path() {
+		#[derive(Debug, serde::Deserialize)]
+		#[allow(dead_code)]
+		struct Meta {
+			count: u64,
+		}
+		#[derive(Debug, serde::Deserialize)]
+		#[allow(dead_code)]
+		struct Body {
+			meta: Meta,
+		}
+
+		let body = r#"{"meta":{"count":"abc"}}"#;
+		let de = &mut serde_json::Deserializer::from_str(body);
+		let error = serde_path_to_error::deserialize::<_, Body>(de).unwrap_err();
+		let error = MidgardError::parse("history/swaps", 200, body, error);
+
+		assert_eq!(error.status(), Some(200));
+		assert_eq!(error.endpoint(), Some("history/swaps"));
+		assert_eq!(error.body(), Some(body));
+		match error {
+			MidgardError::Parse { path, .. } => assert_eq!(path, "meta.count"),
+			_ => panic!("expected parse error"),
+		}
+	}
+
+	#[test]
+	fn test_from_status_with_error_payload() {
+		let error = MidgardError::from_status("pool/NOT.A.POOL", 404, r#"{"error":"pool not found"}"#);
+
+		assert_eq!(error.status(), Some(404));
+		assert_eq!(error.server_message(), Some("pool not found"));
+		assert!(matches!(error, MidgardError::Api { .. }));
+	}
+
+	#[test]
+	fn test_from_status_without_error_payload() {
+		let error = MidgardError::from_status("pools", 502, "<html>Bad Gateway</html>");
+
+		assert_eq!(error.status(), Some(502));
+		assert_eq!(error.server_message(), None);
+		assert_eq!(error.body(), Some("<html>Bad Gateway</html>"));
+		assert!(matches!(error, MidgardError::Http { .. }));
+	}
+
+	#[test]
+	fn test_snippet_truncates() {
+		let body = "a".repeat(BODY_SNIPPET_LEN + 10);
+		assert_eq!(snippet(&body).len(), BODY_SNIPPET_LEN + 3);
+		assert_eq!(snippet("short"), "short");
+	}
+}
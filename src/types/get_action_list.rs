@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::PageTokens;
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct GetActionList {
 	/// Address of sender or recipient of any in/out transaction related to the action.
@@ -97,4 +99,71 @@ impl GetActionList {
 	pub fn set_limit(&mut self, limit: u64) {
 		self.limit = limit;
 	}
+
+	/// Returns true if the query walks forward in time, i.e. `from_timestamp`, `from_height` or `prev_page_token` is set.
+	/// Such queries are continued with `prevPageToken`, all others with `nextPageToken`.
+	#[must_use]
+	pub const fn is_forward(&self) -> bool {
+		self.from_timestamp.is_some() || self.from_height.is_some() || self.prev_page_token.is_some()
+	}
+
+	/// Returns the parameters for the page following the one that returned `meta`, or `None` if there is no further page.
+	/// The starting point (`timestamp`, `height`, `from_timestamp`, `from_height`, `offset`) is replaced by the page token.
+	#[must_use]
+	pub fn next_page(&self, meta: &PageTokens) -> Option<Self> {
+		let forward = self.is_forward();
+		let token = if forward { meta.get_prev_page_token() } else { meta.get_next_page_token() }?;
+		let current = if forward { self.prev_page_token } else { self.next_page_token };
+		if current == Some(token) {
+			return None;
+		}
+
+		let mut next = Self { offset: None, next_page_token: None, timestamp: None, height: None, prev_page_token: None, from_timestamp: None, from_height: None, ..self.clone() };
+		if forward {
+			next.prev_page_token = Some(token);
+		} else {
+			next.next_page_token = Some(token);
+		}
+		Some(next)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn page_tokens(next_page_token: Option<u64>, prev_page_token: Option<u64>) -> PageTokens {
+		let mut meta = PageTokens::default();
+		meta.set_next_page_token(next_page_token);
+		meta.set_prev_page_token(prev_page_token);
+		meta
+	}
+
+	#[test]
+	fn test_next_page_walks_back_with_next_page_token() {
+		let mut params = GetActionList::new(vec!["BTC.BTC".to_string()], 50);
+		params.set_timestamp(1_710_527_743);
+
+		let next = params.next_page(&page_tokens(Some(7), Some(9))).unwrap();
+
+		assert_eq!(next.next_page_token, Some(7));
+		assert_eq!(next.prev_page_token, None);
+		assert_eq!(next.timestamp, None);
+		assert_eq!(next.asset, vec!["BTC.BTC".to_string()]);
+		assert!(next.next_page(&page_tokens(Some(7), Some(9))).is_none());
+		assert!(params.next_page(&page_tokens(None, Some(9))).is_none());
+	}
+
+	#[test]
+	fn test_next_page_walks_forward_with_prev_page_token() {
+		let mut params = GetActionList::new(vec!["BTC.BTC".to_string()], 50);
+		params.set_from_height(15_125_786);
+
+		let next = params.next_page(&page_tokens(Some(7), Some(9))).unwrap();
+
+		assert!(next.is_forward());
+		assert_eq!(next.prev_page_token, Some(9));
+		assert_eq!(next.next_page_token, None);
+		assert_eq!(next.from_height, None);
+	}
 }
@@ -0,0 +1,204 @@
+use std::ops::Range;
+
+use chrono::{DateTime, Utc};
+
+use crate::{Interval, MidgardError};
+
+/// Query parameters shared by every history endpoint.
+///
+/// History endpoints have two modes:
+/// * With an interval a series of time buckets is returned. `from` and `to` are rounded to the interval boundaries, and `count` [1..400] limits the number of intervals.
+///   `count` can be combined with `from` or `to`, but not with both.
+/// * Without an interval a single `from..to` search is performed with exact timestamps. `count` is not allowed in this mode.
+///
+/// `from` defaults to the start of the chain and `to` defaults to now.
+///
+/// # Example
+///
+/// ```rust
+/// use chrono::{Duration, Utc};
+/// use midgard_rs::{HistoryQuery, Interval};
+///
+/// // last 10 days
+/// let query = HistoryQuery::builder().interval(Interval::Day).count(10).build().unwrap();
+///
+/// // exact search for one time frame
+/// let now = Utc::now();
+/// let query = HistoryQuery::builder().range(now - Duration::days(30)..now).build().unwrap();
+///
+/// // count without an interval is rejected
+/// assert!(HistoryQuery::builder().count(10).build().is_err());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistoryQuery {
+	interval: Option<Interval>,
+	count: Option<usize>,
+	from: Option<DateTime<Utc>>,
+	to: Option<DateTime<Utc>>,
+}
+
+impl HistoryQuery {
+	#[must_use]
+	pub fn builder() -> HistoryQueryBuilder {
+		HistoryQueryBuilder::default()
+	}
+
+	#[must_use]
+	pub const fn get_interval(&self) -> Option<&Interval> {
+		self.interval.as_ref()
+	}
+
+	#[must_use]
+	pub const fn get_count(&self) -> Option<usize> {
+		self.count
+	}
+
+	#[must_use]
+	pub const fn get_from(&self) -> Option<&DateTime<Utc>> {
+		self.from.as_ref()
+	}
+
+	#[must_use]
+	pub const fn get_to(&self) -> Option<&DateTime<Utc>> {
+		self.to.as_ref()
+	}
+
+	/// Returns the query parameters in the order Midgard documents them.
+	pub(crate) fn params(&self) -> Vec<(&'static str, String)> {
+		let mut params = vec![];
+		if let Some(interval) = &self.interval {
+			params.push(("interval", interval.to_string()));
+		}
+		if let Some(count) = self.count {
+			params.push(("count", count.to_string()));
+		}
+		if let Some(to) = self.to {
+			params.push(("to", to.timestamp().to_string()));
+		}
+		if let Some(from) = self.from {
+			params.push(("from", from.timestamp().to_string()));
+		}
+		params
+	}
+}
+
+/// Builder for `HistoryQuery`. Parameters are validated in `build`.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryQueryBuilder {
+	interval: Option<Interval>,
+	count: Option<usize>,
+	from: Option<DateTime<Utc>>,
+	to: Option<DateTime<Utc>>,
+}
+
+impl HistoryQueryBuilder {
+	#[must_use]
+	pub const fn interval(mut self, interval: Interval) -> Self {
+		self.interval = Some(interval);
+		self
+	}
+
+	/// Number of intervals to return, [1..400]. Requires an interval, and can't be combined with both `from` and `to`.
+	#[must_use]
+	pub const fn count(mut self, count: usize) -> Self {
+		self.count = Some(count);
+		self
+	}
+
+	#[must_use]
+	pub const fn from(mut self, from: DateTime<Utc>) -> Self {
+		self.from = Some(from);
+		self
+	}
+
+	#[must_use]
+	pub const fn to(mut self, to: DateTime<Utc>) -> Self {
+		self.to = Some(to);
+		self
+	}
+
+	/// Sets both `from` and `to`.
+	#[must_use]
+	pub const fn range(mut self, range: Range<DateTime<Utc>>) -> Self {
+		self.from = Some(range.start);
+		self.to = Some(range.end);
+		self
+	}
+
+	/// # Errors
+	/// 1. `count` outside of [1..400]
+	/// 2. `count` given without an interval
+	/// 3. `count` given together with both `from` and `to`
+	/// 4. `from` later than `to`
+	pub fn build(self) -> Result<HistoryQuery, MidgardError> {
+		if let Some(count) = self.count {
+			if !(1..=400).contains(&count) {
+				return Err(MidgardError::InvalidParameter("count must be in [1..400]".to_string()));
+			}
+			if self.interval.is_none() {
+				return Err(MidgardError::InvalidParameter("count requires an interval".to_string()));
+			}
+			if self.from.is_some() && self.to.is_some() {
+				return Err(MidgardError::InvalidParameter("count can't be combined with both from and to".to_string()));
+			}
+		}
+		if let (Some(from), Some(to)) = (self.from, self.to) {
+			if from > to {
+				return Err(MidgardError::InvalidParameter("from must not be later than to".to_string()));
+			}
+		}
+
+		Ok(HistoryQuery { interval: self.interval, count: self.count, from: self.from, to: self.to })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::TimeZone;
+
+	use super::*;
+
+	#[test]
+	fn test_build_valid() {
+		let from = Utc.timestamp_opt(1_606_780_800, 0).unwrap();
+		let to = Utc.timestamp_opt(1_608_825_600, 0).unwrap();
+		let query = HistoryQuery::builder().interval(Interval::Day).range(from..to).build().unwrap();
+
+		assert_eq!(query.get_interval(), Some(&Interval::Day));
+		assert_eq!(query.get_count(), None);
+		assert_eq!(query.get_from(), Some(&from));
+		assert_eq!(query.get_to(), Some(&to));
+
+		let query = HistoryQuery::builder().interval(Interval::Day).count(10).from(from).build().unwrap();
+		assert_eq!(query.get_count(), Some(10));
+		assert_eq!(query.get_to(), None);
+	}
+
+	#[test]
+	fn test_build_rejects_count_out_of_range() {
+		assert!(matches!(HistoryQuery::builder().interval(Interval::Day).count(0).build(), Err(MidgardError::InvalidParameter(_))));
+		assert!(matches!(HistoryQuery::builder().interval(Interval::Day).count(401).build(), Err(MidgardError::InvalidParameter(_))));
+		assert!(HistoryQuery::builder().interval(Interval::Day).count(400).build().is_ok());
+	}
+
+	#[test]
+	fn test_build_rejects_count_without_interval() {
+		assert!(matches!(HistoryQuery::builder().count(10).build(), Err(MidgardError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_build_rejects_count_with_from_and_to() {
+		let from = Utc.timestamp_opt(1_606_780_800, 0).unwrap();
+		let to = Utc.timestamp_opt(1_608_825_600, 0).unwrap();
+
+		assert!(matches!(HistoryQuery::builder().interval(Interval::Day).count(10).range(from..to).build(), Err(MidgardError::InvalidParameter(_))));
+	}
+
+	#[test]
+	fn test_build_rejects_inverted_range() {
+		let from = Utc.timestamp_opt(1_608_825_600, 0).unwrap();
+		let to = Utc.timestamp_opt(1_606_780_800, 0).unwrap();
+
+		assert!(matches!(HistoryQuery::builder().from(from).to(to).build(), Err(MidgardError::InvalidParameter(_))));
+	}
+}
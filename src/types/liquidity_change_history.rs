@@ -17,8 +17,8 @@ use crate::LiquidityChangeMeta;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct LiquidityChangeHistory {
-	intervals: LiquidityChangeIntervals,
-	meta: LiquidityChangeMeta,
+	pub(crate) intervals: LiquidityChangeIntervals,
+	pub(crate) meta: LiquidityChangeMeta,
 }
 
 impl LiquidityChangeHistory {
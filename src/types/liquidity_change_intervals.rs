@@ -10,7 +10,7 @@ use crate::LiquidityChangeInterval;
 */
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
-pub struct LiquidityChangeIntervals(Vec<LiquidityChangeInterval>);
+pub struct LiquidityChangeIntervals(pub(crate) Vec<LiquidityChangeInterval>);
 
 impl LiquidityChangeIntervals {
 	#[must_use]
@@ -5,6 +5,8 @@ use serde_aux::prelude::*;
 use serde_with::formats::Flexible;
 use serde_with::TimestampSeconds;
 
+use crate::LiquidityChangeInterval;
+
 /*
 
 *** Liquidity Change Meta Scheme ***
@@ -128,4 +130,40 @@ impl LiquidityChangeMeta {
 	pub const fn get_withdraw_volume(&self) -> &u64 {
 		&self.withdraw_volume
 	}
+
+	/// Merges the meta of a later, adjacent page into this one. Counts and volumes are summed and the rune price is taken from `next`.
+	#[allow(clippy::needless_pass_by_value)]
+	pub const fn merge(&mut self, next: Self) {
+		self.add_asset_liquidity_volume = self.add_asset_liquidity_volume.saturating_add(next.add_asset_liquidity_volume);
+		self.add_liquidity_count = self.add_liquidity_count.saturating_add(next.add_liquidity_count);
+		self.add_liquidity_volume = self.add_liquidity_volume.saturating_add(next.add_liquidity_volume);
+		self.add_rune_liquidity_volume = self.add_rune_liquidity_volume.saturating_add(next.add_rune_liquidity_volume);
+		self.end_time = next.end_time;
+		self.net = self.net.saturating_add(next.net);
+		self.rune_price_usd = next.rune_price_usd;
+		self.withdraw_asset_volume = self.withdraw_asset_volume.saturating_add(next.withdraw_asset_volume);
+		self.withdraw_count = self.withdraw_count.saturating_add(next.withdraw_count);
+		self.withdraw_rune_volume = self.withdraw_rune_volume.saturating_add(next.withdraw_rune_volume);
+		self.withdraw_volume = self.withdraw_volume.saturating_add(next.withdraw_volume);
+	}
+}
+
+/// The meta Midgard returns when `interval` is requested on its own.
+impl From<&LiquidityChangeInterval> for LiquidityChangeMeta {
+	fn from(interval: &LiquidityChangeInterval) -> Self {
+		Self {
+			add_asset_liquidity_volume: *interval.get_add_asset_liquidity_volume(),
+			add_liquidity_count: *interval.get_add_liquidity_count(),
+			add_liquidity_volume: *interval.get_add_liquidity_volume(),
+			add_rune_liquidity_volume: *interval.get_add_rune_liquidity_volume(),
+			end_time: *interval.get_end_time(),
+			net: *interval.get_net(),
+			rune_price_usd: *interval.get_rune_price_usd(),
+			start_time: *interval.get_start_time(),
+			withdraw_asset_volume: *interval.get_withdraw_asset_volume(),
+			withdraw_count: *interval.get_withdraw_count(),
+			withdraw_rune_volume: *interval.get_withdraw_rune_volume(),
+			withdraw_volume: *interval.get_withdraw_volume(),
+		}
+	}
 }
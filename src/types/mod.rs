@@ -30,13 +30,16 @@ pub use earnings_intervals::EarningsIntervals;
 pub use earnings_meta::EarningsMeta;
 pub use earnings_pool::EarningsPool;
 pub use earnings_pools::EarningsPools;
-pub use errors::APIError;
+#[cfg(feature = "client")]
+pub use errors::MidgardError;
 pub use get_action_list::GetActionList;
 pub use global_stats::GlobalStats;
 pub use health_info::HealthInfo;
 pub use height_date::HeightDate;
 pub use height_hash::HeightHash;
 pub use height_stamp::HeightStamp;
+#[cfg(feature = "client")]
+pub use history_query::{HistoryQuery, HistoryQueryBuilder};
 pub use interval::Interval;
 pub use known_pool_list::KnownPoolList;
 pub use liquidity_change_history::LiquidityChangeHistory;
@@ -113,6 +116,7 @@ mod earnings_intervals;
 mod earnings_meta;
 mod earnings_pool;
 mod earnings_pools;
+#[cfg(feature = "client")]
 mod errors;
 mod get_action_list;
 mod global_stats;
@@ -120,6 +124,8 @@ mod health_info;
 mod height_date;
 mod height_hash;
 mod height_stamp;
+#[cfg(feature = "client")]
+mod history_query;
 mod interval;
 mod known_pool_list;
 mod liquidity_change_history;
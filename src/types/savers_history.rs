@@ -34,8 +34,8 @@ use crate::SaversHistoryMeta;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct SaversHistory {
-	intervals: SaversHistoryIntervals,
-	meta: SaversHistoryMeta,
+	pub(crate) intervals: SaversHistoryIntervals,
+	pub(crate) meta: SaversHistoryMeta,
 }
 
 impl SaversHistory {
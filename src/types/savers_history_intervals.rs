@@ -10,7 +10,7 @@ use crate::SaversHistoryInterval;
 */
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
-pub struct SaversHistoryIntervals(Vec<SaversHistoryInterval>);
+pub struct SaversHistoryIntervals(pub(crate) Vec<SaversHistoryInterval>);
 
 impl SaversHistoryIntervals {
 	#[must_use]
@@ -4,6 +4,8 @@ use serde_aux::prelude::*;
 use serde_with::formats::Flexible;
 use serde_with::TimestampSeconds;
 
+use crate::SaversHistoryInterval;
+
 /*
 
 *** Saver's History Meta Scheme ***
@@ -91,4 +93,31 @@ impl SaversHistoryMeta {
 	pub const fn get_start_units(&self) -> &u64 {
 		&self.start_units
 	}
+
+	/// Merges the meta of a later, adjacent page into this one. Start values are kept and end values are taken from `next`.
+	#[allow(clippy::needless_pass_by_value)]
+	pub const fn merge(&mut self, next: Self) {
+		*self = Self { start_savers_count: self.start_savers_count, start_savers_depth: self.start_savers_depth, start_time: self.start_time, start_units: self.start_units, ..next };
+	}
+
+	/// Returns the meta of each of `intervals` as if it had been requested on its own, the inverse of `merge`.
+	/// The first interval starts with the start values of this meta, every later one with the end values of the interval before it.
+	#[must_use]
+	pub fn split(&self, intervals: &[SaversHistoryInterval]) -> Vec<Self> {
+		let previous = std::iter::once(None).chain(intervals.iter().map(Some));
+		intervals
+			.iter()
+			.zip(previous)
+			.map(|(interval, previous)| Self {
+				end_savers_count: *interval.get_savers_count(),
+				end_savers_depth: *interval.get_savers_depth(),
+				end_time: *interval.get_end_time(),
+				end_units: *interval.get_savers_units(),
+				start_savers_count: previous.map_or(self.start_savers_count, |previous| *previous.get_savers_count()),
+				start_savers_depth: previous.map_or(self.start_savers_depth, |previous| *previous.get_savers_depth()),
+				start_time: *interval.get_start_time(),
+				start_units: previous.map_or(self.start_units, |previous| *previous.get_savers_units()),
+			})
+			.collect()
+	}
 }
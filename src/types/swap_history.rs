@@ -15,8 +15,8 @@ use crate::SwapMeta;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct SwapHistory {
-	intervals: SwapIntervals,
-	meta: SwapMeta,
+	pub(crate) intervals: SwapIntervals,
+	pub(crate) meta: SwapMeta,
 }
 
 impl SwapHistory {
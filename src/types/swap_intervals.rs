@@ -10,7 +10,7 @@ use crate::SwapInterval;
 */
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
-pub struct SwapIntervals(Vec<SwapInterval>);
+pub struct SwapIntervals(pub(crate) Vec<SwapInterval>);
 
 impl SwapIntervals {
 	#[must_use]
@@ -5,6 +5,8 @@ use serde_aux::prelude::*;
 use serde_with::formats::Flexible;
 use serde_with::TimestampSeconds;
 
+use crate::SwapInterval;
+
 /*
 
 *** Swap Meta Scheme ***
@@ -267,4 +269,98 @@ impl SwapMeta {
 	pub const fn get_total_volume_usd(&self) -> &Option<u64> {
 		&self.total_volume_usd
 	}
+
+	/// Clears the synth mint and redeem volumes in USD. Intervals don't carry them, so they can't be recomputed for a range assembled from single intervals.
+	#[cfg(feature = "client")]
+	pub(crate) const fn clear_synth_volumes_usd(&mut self) {
+		self.synth_mint_volume_usd = None;
+		self.synth_redeem_volume_usd = None;
+	}
+
+	/// Merges the meta of a later, adjacent page into this one.
+	/// Counts, fees and volumes are summed, average slips are weighted by their swap counts and the rune price is taken from `next`.
+	#[allow(clippy::needless_pass_by_value)]
+	pub fn merge(&mut self, next: Self) {
+		self.average_slip = weighted_average(self.average_slip, self.total_count, next.average_slip, next.total_count);
+		self.synth_mint_average_slip = weighted_average(self.synth_mint_average_slip, self.synth_mint_count, next.synth_mint_average_slip, next.synth_mint_count);
+		self.synth_redeem_average_slip = weighted_average(self.synth_redeem_average_slip, self.synth_redeem_count, next.synth_redeem_average_slip, next.synth_redeem_count);
+		self.to_asset_average_slip = weighted_average(self.to_asset_average_slip, self.to_asset_count, next.to_asset_average_slip, next.to_asset_count);
+		self.to_rune_average_slip = weighted_average(self.to_rune_average_slip, self.to_rune_count, next.to_rune_average_slip, next.to_rune_count);
+
+		self.end_time = next.end_time;
+		self.rune_price_usd = next.rune_price_usd;
+
+		self.synth_mint_count = self.synth_mint_count.saturating_add(next.synth_mint_count);
+		self.synth_mint_fees = self.synth_mint_fees.saturating_add(next.synth_mint_fees);
+		self.synth_mint_volume = self.synth_mint_volume.saturating_add(next.synth_mint_volume);
+		self.synth_mint_volume_usd = add_optional(self.synth_mint_volume_usd, next.synth_mint_volume_usd);
+		self.synth_redeem_count = self.synth_redeem_count.saturating_add(next.synth_redeem_count);
+		self.synth_redeem_fees = self.synth_redeem_fees.saturating_add(next.synth_redeem_fees);
+		self.synth_redeem_volume = self.synth_redeem_volume.saturating_add(next.synth_redeem_volume);
+		self.synth_redeem_volume_usd = add_optional(self.synth_redeem_volume_usd, next.synth_redeem_volume_usd);
+		self.to_asset_count = self.to_asset_count.saturating_add(next.to_asset_count);
+		self.to_asset_fees = self.to_asset_fees.saturating_add(next.to_asset_fees);
+		self.to_asset_volume = self.to_asset_volume.saturating_add(next.to_asset_volume);
+		self.to_asset_volume_usd = add_optional(self.to_asset_volume_usd, next.to_asset_volume_usd);
+		self.to_rune_count = self.to_rune_count.saturating_add(next.to_rune_count);
+		self.to_rune_fees = self.to_rune_fees.saturating_add(next.to_rune_fees);
+		self.to_rune_volume = self.to_rune_volume.saturating_add(next.to_rune_volume);
+		self.to_rune_volume_usd = add_optional(self.to_rune_volume_usd, next.to_rune_volume_usd);
+		self.total_count = self.total_count.saturating_add(next.total_count);
+		self.total_fees = self.total_fees.saturating_add(next.total_fees);
+		self.total_volume = self.total_volume.saturating_add(next.total_volume);
+		self.total_volume_usd = add_optional(self.total_volume_usd, next.total_volume_usd);
+	}
+}
+
+/// The meta Midgard returns when `interval` is requested on its own.
+/// Intervals carry no synth mint and redeem volumes in USD, so those are left empty.
+impl From<&SwapInterval> for SwapMeta {
+	fn from(interval: &SwapInterval) -> Self {
+		Self {
+			average_slip: *interval.get_average_slip(),
+			end_time: *interval.get_end_time(),
+			rune_price_usd: *interval.get_rune_price_usd(),
+			start_time: *interval.get_start_time(),
+			synth_mint_average_slip: *interval.get_synth_mint_average_slip(),
+			synth_mint_count: *interval.get_synth_mint_count(),
+			synth_mint_fees: *interval.get_synth_mint_fees(),
+			synth_mint_volume: *interval.get_synth_mint_volume(),
+			synth_mint_volume_usd: None,
+			synth_redeem_average_slip: *interval.get_synth_redeem_average_slip(),
+			synth_redeem_count: *interval.get_synth_redeem_count(),
+			synth_redeem_fees: *interval.get_synth_redeem_fees(),
+			synth_redeem_volume: *interval.get_synth_redeem_volume(),
+			synth_redeem_volume_usd: None,
+			to_asset_average_slip: *interval.get_to_asset_average_slip(),
+			to_asset_count: *interval.get_to_asset_count(),
+			to_asset_fees: *interval.get_to_asset_fees(),
+			to_asset_volume: *interval.get_to_asset_volume(),
+			to_asset_volume_usd: *interval.get_to_asset_volume_usd(),
+			to_rune_average_slip: *interval.get_to_rune_average_slip(),
+			to_rune_count: *interval.get_to_rune_count(),
+			to_rune_fees: *interval.get_to_rune_fees(),
+			to_rune_volume: *interval.get_to_rune_volume(),
+			to_rune_volume_usd: *interval.get_to_rune_volume_usd(),
+			total_count: *interval.get_total_count(),
+			total_fees: *interval.get_total_fees(),
+			total_volume: *interval.get_total_volume(),
+			total_volume_usd: *interval.get_total_volume_usd(),
+		}
+	}
+}
+
+fn weighted_average(average: Decimal, count: u64, next_average: Decimal, next_count: u64) -> Decimal {
+	let total = Decimal::from(count) + Decimal::from(next_count);
+	if total.is_zero() {
+		return average;
+	}
+	(average * Decimal::from(count) + next_average * Decimal::from(next_count)) / total
+}
+
+const fn add_optional(value: Option<u64>, next: Option<u64>) -> Option<u64> {
+	match (value, next) {
+		(Some(value), Some(next)) => Some(value.saturating_add(next)),
+		_ => None,
+	}
 }
@@ -14,8 +14,8 @@ use crate::{TVLIntervals, TVLMeta};
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct TVLHistory {
-	intervals: TVLIntervals,
-	meta: TVLMeta,
+	pub(crate) intervals: TVLIntervals,
+	pub(crate) meta: TVLMeta,
 }
 
 impl TVLHistory {
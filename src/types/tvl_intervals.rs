@@ -10,7 +10,7 @@ use crate::TVLInterval;
 */
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
-pub struct TVLIntervals(Vec<TVLInterval>);
+pub struct TVLIntervals(pub(crate) Vec<TVLInterval>);
 
 impl TVLIntervals {
 	#[must_use]
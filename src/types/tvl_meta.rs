@@ -5,6 +5,7 @@ use serde_aux::prelude::*;
 use serde_with::formats::Flexible;
 use serde_with::TimestampSeconds;
 
+use crate::TVLInterval;
 use crate::TVLPoolDepths;
 
 /*
@@ -66,4 +67,22 @@ impl TVLMeta {
 	pub const fn get_total_value_pooled(&self) -> &u64 {
 		&self.total_value_pooled
 	}
+
+	/// Merges the meta of a later, adjacent page into this one. The meta reports the state at the end of the range, so only the start time is kept.
+	pub fn merge(&mut self, next: Self) {
+		*self = Self { start_time: self.start_time, ..next };
+	}
+}
+
+/// The meta Midgard returns when `interval` is requested on its own.
+impl From<&TVLInterval> for TVLMeta {
+	fn from(interval: &TVLInterval) -> Self {
+		Self {
+			end_time: *interval.get_end_time(),
+			pools_depth: interval.get_pools_depth().clone(),
+			rune_price_usd: *interval.get_rune_price_usd(),
+			start_time: *interval.get_start_time(),
+			total_value_pooled: *interval.get_total_value_pooled(),
+		}
+	}
 }